@@ -0,0 +1,50 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Sub};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Depth(pub i32);
+
+impl Depth {
+    pub const ZERO: Depth = Depth(0);
+    pub const ONE: Depth = Depth(1);
+    pub const MAX: Depth = Depth(127);
+}
+
+impl fmt::Display for Depth {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+impl FromStr for Depth {
+    type Err = ParseIntError;
+
+    fn from_str(value: &str) -> Result<Depth, ParseIntError> {
+        /* Depth in plies, deeper than maximum is searched as deep as possible. */
+        Ok(Depth(value.parse::<i32>()?.min(Depth::MAX.0)))
+    }
+}
+
+impl Add for Depth {
+    type Output = Depth;
+
+    fn add(self, other: Depth) -> Depth {
+        Depth(self.0 + other.0)
+    }
+}
+
+impl Sub for Depth {
+    type Output = Depth;
+
+    fn sub(self, other: Depth) -> Depth {
+        Depth(self.0 - other.0)
+    }
+}
+
+impl AddAssign for Depth {
+    fn add_assign(&mut self, other: Depth) {
+        self.0 += other.0;
+    }
+}
@@ -1,27 +1,50 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Receiver;
-use std::time::Instant;
-
-use chess::{Board, ChessMove, Color, Game, MoveGen, Piece, Square};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::bench::BENCH_POSITIONS;
 use crate::engine_command::EngineCommand;
-use crate::heuristic::Heuristic;
-use crate::piece_value::PieceValue;
 use crate::search_options::SearchOptions;
+use crate::search_thread::SearchThread;
+use crate::transposition_table::TranspositionTable;
 
 pub struct Engine {
-    heuristic: Heuristic,
     receiver: Receiver<EngineCommand>,
-    timer: Option<Instant>,
-    time_for_move: f64,
+    transposition_table: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    nodes: Arc<AtomicUsize>,
+    pondering: Arc<AtomicBool>,
+    search_threads: Vec<SearchThread>,
+    clear_requested: bool,
+    quit_requested: bool,
 }
 
 impl Engine {
     pub fn new(receiver: Receiver<EngineCommand>) -> Engine {
+        let transposition_table =
+            Arc::new(TranspositionTable::new(SearchOptions::default().hash_size));
+        let stop = Arc::new(AtomicBool::new(false));
+        let nodes = Arc::new(AtomicUsize::new(0));
+        let pondering = Arc::new(AtomicBool::new(false));
+        let main_thread = SearchThread::new(
+            0,
+            Arc::clone(&transposition_table),
+            Arc::clone(&stop),
+            Arc::clone(&nodes),
+            Arc::clone(&pondering),
+        );
+
         Engine {
-            heuristic: Heuristic::default(),
             receiver,
-            timer: None,
-            time_for_move: f64::INFINITY,
+            transposition_table,
+            stop,
+            nodes,
+            pondering,
+            search_threads: vec![main_thread],
+            clear_requested: false,
+            quit_requested: false,
         }
     }
 
@@ -31,332 +54,139 @@ impl Engine {
 
             if command.quit {
                 break;
-            } else if command.stop {
+            } else if command.clear {
+                self.reset();
+                continue;
+            } else if command.stop || command.ponder_hit {
                 continue;
             }
 
-            self.initialize_heuristic(&command.search_options);
-            self.start_timer(&command.search_options);
-            self.search(
-                &command.search_options.chess_game,
-                command.search_options.search_depth(),
-            );
-        }
-    }
-
-    fn initialize_heuristic(&mut self, search_options: &SearchOptions) {
-        self.heuristic.fifty_moves_rule = search_options.fifty_moves_rule;
-        self.heuristic.syzygy_path = search_options.syzygy_path.clone();
-    }
-
-    fn check_stop(&self) -> bool {
-        let command = self.receiver.try_recv().unwrap_or(EngineCommand::default());
-        command.stop
-            || command.quit
-            || self.timer.unwrap().elapsed().as_millis() as f64 > self.time_for_move
-    }
-
-    fn search(&mut self, game: &Game, max_depth: f64) {
-        let start = Instant::now();
-
-        // start with random move choice, to be used in case of timeout before first depth is reached
-        let move_gen = MoveGen::new_legal(&game.current_position());
-        let possible_moves: Vec<_> = move_gen.collect();
-        let mut moves: Vec<ChessMove> = vec![
-            possible_moves
-                .get((start.elapsed().as_nanos() / 100) as usize % possible_moves.len())
-                .unwrap()
-                .to_owned(),
-        ];
-
-        let mut depth: f64 = 0.;
-        let mut evaluation: f64;
-        let mut nodes_searched: usize = 0;
-
-        while depth < max_depth {
-            depth += 1.;
-
-            let result = self.negamax(&game, depth, f64::NEG_INFINITY, f64::INFINITY);
-            match result {
-                Ok((eval, pv, nodes)) => {
-                    evaluation = eval;
-                    nodes_searched += nodes;
-                    moves = pv;
-                }
-                Err(_) => {
-                    break;
-                }
+            if command.bench {
+                self.bench(&command.search_options);
+            } else {
+                self.initialize_transposition_table(&command.search_options);
+                self.initialize_search_threads(&command.search_options);
+                self.search(&command.search_options);
             }
 
-            let mut string_moves: Vec<String> = vec![];
-            for chess_move in &moves {
-                string_moves.push(chess_move.to_string());
+            if self.clear_requested {
+                self.reset();
             }
-
-            println!(
-                "info depth {} score cp {} nodes {} nps {} time {} pv {}",
-                depth,
-                evaluation as isize,
-                nodes_searched,
-                (1_000_000. * nodes_searched as f64 / start.elapsed().as_micros() as f64) as usize,
-                start.elapsed().as_millis(),
-                string_moves.join(" ")
-            )
-        }
-
-        println!("bestmove {}", &moves[0].to_string());
-    }
-
-    fn negamax(
-        &self,
-        game: &Game,
-        depth: f64,
-        mut alpha: f64,
-        beta: f64,
-    ) -> Result<(f64, Vec<ChessMove>, usize), &'static str> {
-        if self.check_stop() {
-            return Err("Calculation stopped.");
-        }
-
-        let mut nodes_searched: usize = 1;
-
-        if game.result().is_some() {
-            let result = game.result().unwrap();
-            let color = game.side_to_move();
-            return Ok((
-                self.heuristic.evaluate_result(result, color),
-                vec![],
-                nodes_searched,
-            ));
-        }
-        if game.can_declare_draw() {
-            return Ok((0.0, vec![], nodes_searched));
-        }
-        if depth == 0. {
-            let evaluation: f64;
-            let result = self.quiescence(game, alpha, beta);
-            match result {
-                Ok((eval, nodes)) => {
-                    evaluation = eval;
-                    nodes_searched += nodes;
-                }
-                Err(message) => return Err(message),
+            if self.quit_requested {
+                break;
             }
-            return Ok((evaluation, vec![], nodes_searched));
         }
+    }
 
-        let legal_moves = MoveGen::new_legal(&game.current_position()).collect();
-        let ordered_moves = self.order_moves(&game.current_position(), legal_moves);
-        let mut best_moves: Vec<ChessMove> = vec![];
-        let mut moves: Vec<ChessMove>;
-        let mut current_game: Game;
-        let mut evaluation: f64;
-
-        for chess_move in ordered_moves {
-            current_game = game.clone();
-            current_game.make_move(chess_move);
-
-            let result = self.negamax(&current_game, depth - 1., -beta, -alpha);
-            match result {
-                Ok((eval, pv, nodes)) => {
-                    evaluation = eval;
-                    nodes_searched += nodes;
-                    moves = pv;
-                }
-                Err(message) => return Err(message),
-            }
-
-            evaluation *= -1.;
-            moves.insert(0, chess_move);
-
-            if evaluation >= beta {
-                return Ok((beta, vec![], nodes_searched));
-            }
-            if evaluation > alpha {
-                alpha = evaluation;
-                best_moves = moves;
+    pub fn bench(&mut self, search_options: &SearchOptions) {
+        /* Search fixed positions to fixed depth with single thread, each from clean state,
+        so that total node count is reproducible signature of search behaviour. */
+        let mut options = search_options.clone();
+        options.threads = 1;
+        let start = Instant::now();
+        let mut nodes = 0;
+
+        for fen in BENCH_POSITIONS {
+            let mut args = vec![String::from("fen")];
+            args.extend(fen.split_whitespace().map(str::to_string));
+            options
+                .set_position(&args)
+                .expect("Bench position is not valid.");
+
+            self.reset();
+            self.initialize_transposition_table(&options);
+            self.initialize_search_threads(&options);
+            self.search(&options);
+            nodes += self.nodes.load(Ordering::Relaxed);
+
+            if self.quit_requested {
+                break;
             }
         }
 
-        Ok((alpha, best_moves, nodes_searched))
+        let elapsed = start.elapsed();
+        println!("Nodes searched: {}", nodes);
+        println!("Time: {} ms", elapsed.as_millis());
+        println!(
+            "Nodes/second: {}",
+            (nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)) as usize
+        );
     }
 
-    fn quiescence(
-        &self,
-        game: &Game,
-        mut alpha: f64,
-        beta: f64,
-    ) -> Result<(f64, usize), &'static str> {
-        if self.check_stop() {
-            return Err("Calculation stopped.");
-        }
-
-        if game.result().is_some() {
-            let result = game.result().unwrap();
-            let color = game.side_to_move();
-            return Ok((0.95 * self.heuristic.evaluate_result(result, color), 0));
+    fn reset(&mut self) {
+        /* Drop all search state kept between searches, so that games are independent. */
+        self.transposition_table.clear();
+        for search_thread in &mut self.search_threads {
+            search_thread.reset();
         }
-        if game.can_declare_draw() {
-            return Ok((0.0, 0));
-        }
-
-        let evaluation = 0.95 * self.heuristic.evaluate_position(game);
-
-        if evaluation >= beta {
-            return Ok((beta, 0));
-        }
-
-        let use_delta_pruning = game
-            .current_position()
-            .combined()
-            .collect::<Vec<Square>>()
-            .len()
-            > 8;
-        let piece_value = PieceValue::default();
-
-        if use_delta_pruning && evaluation < alpha - piece_value.queen_value {
-            return Ok((alpha, 0));
-        }
-
-        if evaluation > alpha {
-            alpha = evaluation;
-        }
-
-        let mut nodes_searched: usize = 0;
-        for (chess_move, is_capture, is_en_passant) in self.get_captures_and_checks(&game) {
-            if use_delta_pruning && is_en_passant && (evaluation + piece_value.pawn_value < alpha) {
-                continue;
-            } else if use_delta_pruning
-                && is_capture
-                && (piece_value.get_piece_value(
-                    game.current_position()
-                        .piece_on(chess_move.get_dest())
-                        .unwrap(),
-                ) + piece_value.pawn_value
-                    < alpha)
-            {
-                continue;
-            }
-
-            let mut current_game = game.clone();
-            current_game.make_move(chess_move);
-            nodes_searched += 1;
-
-            let score: f64;
-            let result = self.quiescence(&current_game, -beta, -alpha);
-            match result {
-                Ok((eval, nodes)) => {
-                    score = -eval;
-                    nodes_searched += nodes;
-                }
-                Err(message) => return Err(message),
-            }
-
-            if score >= beta {
-                return Ok((beta, nodes_searched));
-            }
-            if score > alpha {
-                alpha = score;
-            }
-        }
-
-        Ok((alpha, nodes_searched))
+        self.clear_requested = false;
     }
 
-    fn get_captures_and_checks(&self, game: &Game) -> Vec<(ChessMove, bool, bool)> {
-        let mut captures_and_checks: Vec<(ChessMove, bool, bool)> = vec![];
-        let legal_moves = MoveGen::new_legal(&game.current_position()).collect();
-        let ordered_moves = self.order_moves(&game.current_position(), legal_moves);
-        let board = game.current_position();
-
-        for chess_move in ordered_moves {
-            let board_after_move = board.make_move_new(chess_move);
-
-            let captured_piece = board.piece_on(chess_move.get_dest()) != None;
-            let is_check = board_after_move.checkers().collect::<Vec<Square>>().len() != 0;
-
-            let en_passant_capture = board.piece_on(chess_move.get_source()).unwrap()
-                == Piece::Pawn
-                && (chess_move.get_source().get_rank() != chess_move.get_dest().get_rank())
-                && (chess_move.get_source().get_file() != chess_move.get_dest().get_file());
-
-            if captured_piece || en_passant_capture || is_check {
-                captures_and_checks.push((chess_move, captured_piece, en_passant_capture));
-            }
+    fn initialize_transposition_table(&mut self, search_options: &SearchOptions) {
+        if self.transposition_table.size_mb() != search_options.hash_size {
+            self.transposition_table = Arc::new(TranspositionTable::new(search_options.hash_size));
         }
-
-        captures_and_checks
     }
 
-    fn start_timer(&mut self, search_options: &SearchOptions) {
-        /* Start timer to check elapsed time and stop it over limit. */
-        self.timer = Some(Instant::now());
-        self.time_for_move = f64::INFINITY;
+    fn initialize_search_threads(&mut self, search_options: &SearchOptions) {
+        let threads = search_options.threads.max(1);
+        while self.search_threads.len() < threads {
+            self.search_threads.push(SearchThread::new(
+                self.search_threads.len(),
+                Arc::clone(&self.transposition_table),
+                Arc::clone(&self.stop),
+                Arc::clone(&self.nodes),
+                Arc::clone(&self.pondering),
+            ));
+        }
+        self.search_threads.truncate(threads);
 
-        match (
-            search_options.chess_game.side_to_move(),
-            search_options.move_time,
-            search_options.white_time,
-            search_options.white_increment,
-            search_options.black_time,
-            search_options.black_increment,
-        ) {
-            (_, 0, 0, 0, 0, 0) => return,
-            (_, move_time, _, _, _, _) if move_time > 0 => {
-                self.time_for_move = move_time as f64;
-            }
-            (Color::White, _, white_time, 0, _, _) if white_time > 0 => {
-                self.time_for_move = 0.05 * (white_time as f64 - search_options.move_overhead);
-            }
-            (Color::White, _, white_time, white_increment, _, _) if white_time > 0 => {
-                self.time_for_move = (0.1 * white_time as f64 + white_increment as f64
-                    - search_options.move_overhead)
-                    .min(white_time as f64 - search_options.move_overhead);
-            }
-            (Color::Black, _, _, _, black_time, 0) if black_time > 0 => {
-                self.time_for_move = 0.05 * (black_time as f64 - search_options.move_overhead);
-            }
-            (Color::Black, _, _, _, black_time, black_increment) if black_time > 0 => {
-                self.time_for_move = (0.1 * black_time as f64 + black_increment as f64
-                    - search_options.move_overhead)
-                    .min(black_time as f64 - search_options.move_overhead);
-            }
-            _ => panic!("Incorrect time options."),
+        for search_thread in &mut self.search_threads {
+            search_thread.initialize(search_options, &self.transposition_table);
         }
+        self.stop.store(false, Ordering::Relaxed);
+        self.nodes.store(0, Ordering::Relaxed);
+        self.pondering
+            .store(search_options.pondering, Ordering::Relaxed);
     }
 
-    fn order_moves(&self, board: &Board, moves: Vec<ChessMove>) -> Vec<ChessMove> {
-        let mut scored_moves: Vec<(ChessMove, i32)> = vec![];
-        let piece_value = PieceValue::default();
-
-        for mv in moves {
-            let mut score = 0;
-            let from = mv.get_source();
-            let to = mv.get_dest();
-            let attacker = board.piece_on(from);
-            let victim = board.piece_on(to);
-
-            // MVV-LVA scoring
-            if let (Some(att), Some(vic)) = (attacker, victim) {
-                score += 10 * piece_value.get_piece_value(vic) as i32
-                    - piece_value.get_piece_value(att) as i32;
-            }
-
-            // Promotion bonus
-            if let Some(promo) = mv.get_promotion() {
-                score += 5 * piece_value.get_piece_value(promo) as i32;
-            }
-
-            // Check bonus
-            let new_board = board.make_move_new(mv);
-            if new_board.checkers().0 != 0 {
-                score += 1;
+    fn search(&mut self, search_options: &SearchOptions) {
+        /* Lazy SMP -> all threads search same root sharing transposition table,
+        main thread reports result and stops helpers once it is done. */
+        let board = &search_options.board;
+        let history = &search_options.history;
+        let max_depth = search_options.search_depth();
+        let (main_thread, helper_threads) = self.search_threads.split_first_mut().unwrap();
+        let receiver = &self.receiver;
+        let stop = &self.stop;
+        let pondering = &self.pondering;
+        let mut clear_requested = false;
+        let mut quit_requested = false;
+
+        thread::scope(|scope| {
+            for helper_thread in helper_threads {
+                scope.spawn(move || helper_thread.search(board, history, max_depth));
+            }
+            let main_handle = scope.spawn(move || main_thread.search(board, history, max_depth));
+
+            while !main_handle.is_finished() {
+                let Ok(command) = receiver.recv_timeout(Duration::from_millis(1)) else {
+                    continue;
+                };
+                // clear waits for search end, infinite or ponder search may only end on stop
+                clear_requested |= command.clear;
+                quit_requested |= command.quit;
+                // ponder hit turns pondering into normal search, timed from now on
+                if command.ponder_hit {
+                    pondering.store(false, Ordering::Relaxed);
+                }
+                if command.stop || command.quit {
+                    stop.store(true, Ordering::Relaxed);
+                }
             }
+        });
 
-            scored_moves.push((mv, score));
-        }
-
-        scored_moves.sort_by(|a, b| b.1.cmp(&a.1));
-        scored_moves.into_iter().map(|(mv, _)| mv).collect()
+        self.clear_requested |= clear_requested;
+        self.quit_requested |= quit_requested;
     }
 }
@@ -4,22 +4,20 @@ pub struct EngineCommand {
     pub search_options: SearchOptions,
     pub stop: bool,
     pub quit: bool,
+    pub clear: bool,
+    pub ponder_hit: bool,
+    pub bench: bool,
 }
 
 impl EngineCommand {
-    pub fn default() -> EngineCommand {
-        EngineCommand {
-            search_options: SearchOptions::default(),
-            stop: false,
-            quit: false,
-        }
-    }
-
     pub fn go(options: SearchOptions) -> EngineCommand {
         EngineCommand {
             search_options: options,
             stop: false,
             quit: false,
+            clear: false,
+            ponder_hit: false,
+            bench: false,
         }
     }
 
@@ -28,6 +26,9 @@ impl EngineCommand {
             search_options: SearchOptions::default(),
             stop: true,
             quit: false,
+            clear: false,
+            ponder_hit: false,
+            bench: false,
         }
     }
 
@@ -36,6 +37,42 @@ impl EngineCommand {
             search_options: SearchOptions::default(),
             stop: true,
             quit: true,
+            clear: false,
+            ponder_hit: false,
+            bench: false,
+        }
+    }
+
+    pub fn clear() -> EngineCommand {
+        EngineCommand {
+            search_options: SearchOptions::default(),
+            stop: false,
+            quit: false,
+            clear: true,
+            ponder_hit: false,
+            bench: false,
+        }
+    }
+
+    pub fn ponder_hit() -> EngineCommand {
+        EngineCommand {
+            search_options: SearchOptions::default(),
+            stop: false,
+            quit: false,
+            clear: false,
+            ponder_hit: true,
+            bench: false,
+        }
+    }
+
+    pub fn bench(options: SearchOptions) -> EngineCommand {
+        EngineCommand {
+            search_options: options,
+            stop: false,
+            quit: false,
+            clear: false,
+            ponder_hit: false,
+            bench: true,
         }
     }
 }
@@ -1,37 +1,37 @@
 use std::path::PathBuf;
 
-use chess::{Color, Game, GameResult, Piece, Square};
+use chess::{ALL_PIECES, Board, BoardStatus, Color, Piece, Square};
 
-use crate::piece_value::PieceValue;
+use crate::pawn_structure::PawnTable;
+use crate::piece_square_table::PieceSquareTables;
+
+use crate::score::Score;
+use crate::tapered_score::TaperedScore;
+
+const KNIGHT_PHASE: u32 = 1;
+const BISHOP_PHASE: u32 = 1;
+const ROOK_PHASE: u32 = 2;
+const QUEEN_PHASE: u32 = 4;
 
 #[derive(Debug, Clone)]
 pub struct Heuristic {
     pub fifty_moves_rule: bool,
     pub syzygy_path: Option<PathBuf>,
-
-    draw_value: f64,
-    loss_value: f64,
-    win_value: f64,
-
-    pawn_rank_weight: f64,
-    pawn_file_weight: f64,
-    pawn_center_weight: f64,
-    pawn_distance_weight: f64,
-
-    knight_center_weight: f64,
-    knight_distance_weight: f64,
-
-    bishop_center_weight: f64,
-    bishop_distance_weight: f64,
-
-    rook_center_weight: f64,
-    rook_distance_weight: f64,
-
-    queen_center_weight: f64,
-    queen_distance_weight: f64,
-
-    king_center_weight: f64,
-    king_distance_weight: f64,
+    pub piece_square_tables: PieceSquareTables,
+    pawn_table: PawnTable,
+
+    pawn_value: TaperedScore,
+    knight_value: TaperedScore,
+    bishop_value: TaperedScore,
+    rook_value: TaperedScore,
+    queen_value: TaperedScore,
+
+    pawn_distance_weight: TaperedScore,
+    knight_distance_weight: TaperedScore,
+    bishop_distance_weight: TaperedScore,
+    rook_distance_weight: TaperedScore,
+    queen_distance_weight: TaperedScore,
+    king_distance_weight: TaperedScore,
 }
 
 impl Heuristic {
@@ -39,148 +39,83 @@ impl Heuristic {
         Heuristic {
             fifty_moves_rule: true,
             syzygy_path: None,
-
-            draw_value: 0.,       // [cp]
-            loss_value: -120_00., // [cp]
-            win_value: 120_00.,   // [cp]
-
-            pawn_rank_weight: 7.,
-            pawn_file_weight: 5.,
-            pawn_center_weight: 5.,
-            pawn_distance_weight: 5.,
-
-            knight_center_weight: 7.,
-            knight_distance_weight: 8.,
-
-            bishop_center_weight: 5.,
-            bishop_distance_weight: 8.,
-
-            rook_center_weight: 8.,
-            rook_distance_weight: 5.,
-
-            queen_center_weight: 2.,
-            queen_distance_weight: 8.,
-
-            king_center_weight: 8.,
-            king_distance_weight: 5.,
+            piece_square_tables: PieceSquareTables::default(),
+            pawn_table: PawnTable::new(),
+
+            pawn_value: TaperedScore::new(95, 115),
+            knight_value: TaperedScore::new(355, 335),
+            bishop_value: TaperedScore::new(370, 370),
+            rook_value: TaperedScore::new(535, 575),
+            queen_value: TaperedScore::new(950, 960),
+
+            pawn_distance_weight: TaperedScore::new(3, 5),
+            knight_distance_weight: TaperedScore::new(8, 4),
+            bishop_distance_weight: TaperedScore::new(8, 4),
+            rook_distance_weight: TaperedScore::new(5, 5),
+            queen_distance_weight: TaperedScore::new(8, 6),
+            king_distance_weight: TaperedScore::new(2, 6),
         }
     }
 
-    pub fn evaluate_result(&self, result: GameResult, color: Color) -> f64 {
-        /* Evaluate game result and return value in centi-pawns. */
-        match (result, color) {
-            (GameResult::WhiteCheckmates, Color::White) => self.win_value,
-            (GameResult::WhiteCheckmates, Color::Black) => self.loss_value,
-            (GameResult::BlackCheckmates, Color::Black) => self.win_value,
-            (GameResult::BlackCheckmates, Color::White) => self.loss_value,
-
-            (GameResult::Stalemate, _) => self.draw_value,
-            (GameResult::DrawAccepted, _) => self.draw_value,
-            (GameResult::DrawDeclared, _) => self.draw_value,
+    pub fn clear_pawn_table(&mut self) {
+        self.pawn_table.clear();
+    }
 
-            (GameResult::WhiteResigns, Color::White) => self.loss_value,
-            (GameResult::WhiteResigns, Color::Black) => self.win_value,
-            (GameResult::BlackResigns, Color::White) => self.win_value,
-            (GameResult::BlackResigns, Color::Black) => self.loss_value,
+    pub fn evaluate_result(&self, status: BoardStatus, ply: usize) -> Score {
+        /* Evaluate finished game reached at given ply from root for side to move,
+        the later the loss the better. */
+        match status {
+            BoardStatus::Checkmate => Score::mated_in(ply),
+            BoardStatus::Stalemate | BoardStatus::Ongoing => Score::DRAW,
         }
     }
 
-    pub fn evaluate_position(&self, game: &Game) -> f64 {
-        /* Evaluate board and return value in centi-pawns. */
+    pub fn evaluate_position(&mut self, board: &Board) -> Score {
+        /* Evaluate board and return value in centi-pawns, interpolated between middlegame
+        and endgame values by remaining material. */
         // TODO: syzygy tablebase evaluation
 
-        let board = game.current_position();
-        let pawns = board.pieces(Piece::Pawn);
-        let knights = board.pieces(Piece::Knight);
-        let bishops = board.pieces(Piece::Bishop);
-        let rooks = board.pieces(Piece::Rook);
-        let queens = board.pieces(Piece::Queen);
-        let kings = board.pieces(Piece::King);
-
-        let mut player_value: f64 = 0.;
-        let mut opponent_value: f64 = 0.;
-        let piece_value = PieceValue::default();
-
-        for square in pawns.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += piece_value.pawn_value;
-                player_value += self.pawn_bonus(
-                    square,
-                    board.side_to_move(),
-                    board.king_square(!board.side_to_move()),
-                );
-            } else {
-                opponent_value += piece_value.pawn_value;
-                opponent_value += self.pawn_bonus(
-                    square,
-                    !board.side_to_move(),
-                    board.king_square(board.side_to_move()),
-                );
-            }
-        }
-
-        for square in knights.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += piece_value.knight_value;
-                player_value += self.knight_bonus(square, board.king_square(!board.side_to_move()))
-            } else {
-                opponent_value += piece_value.knight_value;
-                opponent_value += self.knight_bonus(square, board.king_square(board.side_to_move()))
-            }
-        }
-
-        for square in bishops.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += piece_value.bishop_value;
-                player_value += self.bishop_bonus(square, board.king_square(!board.side_to_move()))
-            } else {
-                opponent_value += piece_value.bishop_value;
-                opponent_value += self.bishop_bonus(square, board.king_square(board.side_to_move()))
-            }
-        }
-
-        for square in rooks.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += piece_value.rook_value;
-                player_value += self.rook_bonus(square, board.king_square(!board.side_to_move()))
-            } else {
-                opponent_value += piece_value.rook_value;
-                opponent_value += self.rook_bonus(square, board.king_square(board.side_to_move()))
+        let mut player_value = TaperedScore::ZERO;
+        let mut opponent_value = TaperedScore::ZERO;
+
+        for piece in ALL_PIECES {
+            let (value, distance_weight) = self.piece_weights(piece);
+            for square in *board.pieces(piece) {
+                let color = board.color_on(square).unwrap();
+                let opponent_king = board.king_square(!color);
+                let piece_value =
+                    value + self.piece_bonus(piece, square, color, opponent_king, distance_weight);
+                if color == board.side_to_move() {
+                    player_value += piece_value;
+                } else {
+                    opponent_value += piece_value;
+                }
             }
         }
 
-        for square in queens.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += piece_value.queen_value;
-                player_value += self.queen_bonus(square, board.king_square(!board.side_to_move()))
-            } else {
-                opponent_value += piece_value.queen_value;
-                opponent_value += self.queen_bonus(square, board.king_square(board.side_to_move()))
-            }
+        // pawn structure is scored from white's point of view
+        let pawn_structure = self.pawn_table.evaluate(board);
+        match board.side_to_move() {
+            Color::White => player_value += pawn_structure,
+            Color::Black => opponent_value += pawn_structure,
         }
 
-        for square in kings.into_iter() {
-            if board.color_on(square).unwrap() == board.side_to_move() {
-                player_value += self.king_bonus(
-                    square,
-                    board.king_square(!board.side_to_move()),
-                    queens.count() == 0,
-                )
-            } else {
-                opponent_value += self.king_bonus(
-                    square,
-                    board.king_square(board.side_to_move()),
-                    queens.count() == 0,
-                )
-            }
-        }
+        (player_value - opponent_value).interpolate(Heuristic::game_phase(board))
+    }
 
-        player_value - opponent_value
+    fn game_phase(board: &Board) -> i32 {
+        /* Phase by non-pawn material left, maximal in starting position and zero with only
+        kings and pawns, promotions may push it over maximum. */
+        let phase = board.pieces(Piece::Knight).popcnt() * KNIGHT_PHASE
+            + board.pieces(Piece::Bishop).popcnt() * BISHOP_PHASE
+            + board.pieces(Piece::Rook).popcnt() * ROOK_PHASE
+            + board.pieces(Piece::Queen).popcnt() * QUEEN_PHASE;
+        (phase as i32).min(TaperedScore::MAX_PHASE)
     }
 
     fn _pawn_advantage_to_win_probability(pawn_advantage: f64) -> f64 {
         /* Calculate winning probability given pawn advantage. */
-        return 1. / (1. + (10_f64).powf(-pawn_advantage / 4.));
+        1. / (1. + (10_f64).powf(-pawn_advantage / 4.))
     }
 
     fn _win_probability_to_pawn_advantage(mut win_probability: f64) -> f64 {
@@ -193,130 +128,37 @@ impl Heuristic {
         4. * (win_probability / (1. - win_probability)).log10()
     }
 
-    fn pawn_bonus(&self, pawn: Square, color: Color, opponent_king: Square) -> f64 {
-        /* Evaluation bonus for positions of pawns on board. */
-
-        // rank bonus -> the further forward the pawn, the more of a bonus
-        let mut p_bonus =
-            (pawn.get_rank().to_index() as f64 - color.to_second_rank().to_index() as f64).abs()
-                * self.pawn_rank_weight;
-
-        // file penalty -> central files take none, the closer to rim the less pawn's value
-        if pawn.get_file().to_index() < 3 {
-            p_bonus -= (3. - pawn.get_file().to_index() as f64) * self.pawn_file_weight;
-        } else if pawn.get_file().to_index() > 4 {
-            p_bonus -= (pawn.get_file().to_index() as f64 - 4.) * self.pawn_file_weight;
-        }
-
-        // occupying center bonus
-        p_bonus += Heuristic::occupying_center_bonus(pawn, self.pawn_center_weight);
-        // distance from king bonus
-        p_bonus +=
-            Heuristic::distance_from_king_bonus(pawn, opponent_king, self.pawn_distance_weight);
-
-        p_bonus
-    }
-
-    fn knight_bonus(&self, knight: Square, opponent_king: Square) -> f64 {
-        /* Evaluation bonus for positions knights on board. */
-
-        // occupying center bonus
-        let mut k_bonus = Heuristic::occupying_center_bonus(knight, self.knight_center_weight);
-        // distance from king bonus
-        k_bonus +=
-            Heuristic::distance_from_king_bonus(knight, opponent_king, self.knight_distance_weight);
-
-        k_bonus
-    }
-
-    fn bishop_bonus(&self, bishop: Square, opponent_king: Square) -> f64 {
-        /* Evaluation bonus for positions of bishops on board. */
-
-        // occupying center bonus
-        let mut b_bonus = Heuristic::occupying_center_bonus(bishop, self.bishop_center_weight);
-        // distance from king bonus
-        b_bonus +=
-            Heuristic::distance_from_king_bonus(bishop, opponent_king, self.bishop_distance_weight);
-
-        b_bonus
-    }
-
-    fn rook_bonus(&self, rook: Square, opponent_king: Square) -> f64 {
-        /* Evaluation bonus for positions of rooks on board. */
-        let mut r_bonus = 0.;
-
-        // occupying center files bonus
-        if (3usize..5usize).contains(&rook.get_file().to_index()) {
-            r_bonus += 3. * self.rook_center_weight;
-        } else if (2usize..6usize).contains(&rook.get_file().to_index()) {
-            r_bonus += 2. * self.rook_center_weight;
-        } else if (1usize..7usize).contains(&rook.get_file().to_index()) {
-            r_bonus += self.rook_center_weight;
-        }
-
-        // distance from king bonus
-        r_bonus +=
-            Heuristic::distance_from_king_bonus(rook, opponent_king, self.rook_distance_weight);
-
-        r_bonus
-    }
-
-    fn queen_bonus(&self, queen: Square, opponent_king: Square) -> f64 {
-        /* Evaluation bonus for positions of queens on board. */
-
-        // occupying center bonus
-        let mut q_bonus = Heuristic::occupying_center_bonus(queen, self.queen_center_weight);
-        // distance from king bonus
-        q_bonus +=
-            Heuristic::distance_from_king_bonus(queen, opponent_king, self.queen_distance_weight);
-
-        q_bonus
-    }
-
-    fn king_bonus(&self, king: Square, opponent_king: Square, no_queens: bool) -> f64 {
-        /* Evaluation bonus for positions of king on board. */
-        let king_center_weight: f64;
-        if no_queens {
-            king_center_weight = self.king_center_weight;
-        } else {
-            king_center_weight = -self.knight_center_weight;
+    fn piece_weights(&self, piece: Piece) -> (TaperedScore, TaperedScore) {
+        /* Material value and weight of distance from opponent's king, king has no value. */
+        match piece {
+            Piece::Pawn => (self.pawn_value, self.pawn_distance_weight),
+            Piece::Knight => (self.knight_value, self.knight_distance_weight),
+            Piece::Bishop => (self.bishop_value, self.bishop_distance_weight),
+            Piece::Rook => (self.rook_value, self.rook_distance_weight),
+            Piece::Queen => (self.queen_value, self.queen_distance_weight),
+            Piece::King => (TaperedScore::ZERO, self.king_distance_weight),
         }
-
-        // occupying center bonus
-        let mut k_bonus = Heuristic::occupying_center_bonus(king, king_center_weight);
-
-        // distance from king bonus
-        k_bonus +=
-            Heuristic::distance_from_king_bonus(king, opponent_king, self.king_distance_weight);
-
-        k_bonus
     }
 
-    fn occupying_center_bonus(piece: Square, bonus: f64) -> f64 {
-        /* Bonus for occupying squares close to center. */
-        if (3usize..5usize).contains(&piece.get_rank().to_index())
-            && (3usize..5usize).contains(&piece.get_file().to_index())
-        {
-            return 3. * bonus;
-        }
-        if (2usize..6usize).contains(&piece.get_rank().to_index())
-            && (3usize..5usize).contains(&piece.get_file().to_index())
-        {
-            return 2. * bonus;
-        }
-        if (1usize..7usize).contains(&piece.get_rank().to_index())
-            && (3usize..5usize).contains(&piece.get_file().to_index())
-        {
-            return bonus;
-        }
-        0.
+    fn piece_bonus(
+        &self,
+        piece: Piece,
+        square: Square,
+        color: Color,
+        opponent_king: Square,
+        distance_weight: TaperedScore,
+    ) -> TaperedScore {
+        /* Evaluation bonus for position of piece on board, from its square and its distance
+        from opponent's king. */
+        self.piece_square_tables.get(piece, color, square)
+            + Heuristic::distance_from_king_bonus(square, opponent_king, distance_weight)
     }
 
-    fn distance_from_king_bonus(piece: Square, king: Square, bonus: f64) -> f64 {
+    fn distance_from_king_bonus(piece: Square, king: Square, bonus: TaperedScore) -> TaperedScore {
         /* Bonus for distance from opponent's king. */
-        let distance = (piece.get_rank().to_index() as f64 - king.get_rank().to_index() as f64)
+        let distance = (piece.get_rank().to_index() as i32 - king.get_rank().to_index() as i32)
             .abs()
-            + (piece.get_file().to_index() as f64 - king.get_file().to_index() as f64).abs();
-        14. / distance * bonus - bonus
+            + (piece.get_file().to_index() as i32 - king.get_file().to_index() as i32).abs();
+        bonus * 14 / distance - bonus
     }
 }
@@ -1,21 +1,45 @@
+use std::env;
 use std::sync::mpsc::channel;
 use std::thread;
 
+use crate::bench::bench_depth;
 use crate::engine::Engine;
 use crate::infra::capitalize_first_letter;
+use crate::piece_square_table::PieceSquareTables;
+use crate::search_options::SearchOptions;
 use crate::uci_protocol::UciProtocol;
 use crate::version::display_version;
 
+mod bench;
+mod depth;
 mod engine;
 mod engine_command;
 mod heuristic;
 mod infra;
+mod pawn_structure;
+mod perft;
+mod piece_square_table;
 mod piece_value;
+mod position_history;
+mod score;
 mod search_options;
+mod search_thread;
+mod static_exchange;
+mod tapered_score;
+mod transposition_table;
+mod uci_error;
 mod uci_protocol;
 mod version;
 
 fn main() {
+    // tables as command line argument prints default piece-square tables in loadable format,
+    // without version line so that output can be saved as file directly
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|r| r == "tables") {
+        print!("{}", PieceSquareTables::generate());
+        return;
+    }
+
     println!(
         "{} {} by {}",
         capitalize_first_letter(env!("CARGO_PKG_NAME")),
@@ -25,6 +49,20 @@ fn main() {
 
     let (tx, rx) = channel();
     let mut engine = Engine::new(rx);
+
+    // bench [depth] as command line argument runs bench with default options and exits
+    if args.first().is_some_and(|r| r == "bench") {
+        match bench_depth(&args[1..]) {
+            Ok(depth) => {
+                let mut options = SearchOptions::default();
+                options.set_bench_parameters(depth);
+                engine.bench(&options);
+            }
+            Err(error) => println!("error: {}", error),
+        }
+        return;
+    }
+
     thread::spawn(move || engine.start());
 
     UciProtocol::new(tx).uci_loop();
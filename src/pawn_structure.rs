@@ -0,0 +1,344 @@
+use chess::{
+    ALL_COLORS, BitBoard, Board, Color, EMPTY, Piece, Square, get_adjacent_files, get_file,
+    get_pawn_attacks, get_rank,
+};
+
+use crate::tapered_score::TaperedScore;
+
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+const DOUBLED_PAWN_PENALTY: TaperedScore = TaperedScore::new(-10, -20);
+const ISOLATED_PAWN_PENALTY: TaperedScore = TaperedScore::new(-10, -15);
+const BACKWARD_PAWN_PENALTY: TaperedScore = TaperedScore::new(-8, -10);
+const CONNECTED_PAWN_BONUS: TaperedScore = TaperedScore::new(8, 6);
+// by rank counted from own side, pawns never stand on first or last rank
+const PASSED_PAWN_BONUS: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(5, 10),
+    TaperedScore::new(10, 15),
+    TaperedScore::new(15, 25),
+    TaperedScore::new(25, 45),
+    TaperedScore::new(45, 75),
+    TaperedScore::new(70, 120),
+    TaperedScore::new(0, 0),
+];
+const PASSED_PAWN_OPPONENT_KING_WEIGHT: i32 = 3;
+const PASSED_PAWN_OWN_KING_WEIGHT: i32 = 1;
+
+// pawn-only zobrist keys, board's hash covers all pieces and cannot be used for pawn table
+const PAWN_KEYS: [[u64; 64]; 2] = generate_pawn_keys();
+
+const fn generate_pawn_keys() -> [[u64; 64]; 2] {
+    /* Fixed pseudo-random keys from splitmix64 generator, same in every run. */
+    let mut keys = [[0; 64]; 2];
+    let mut state: u64 = 0x5741_4e50_4157_4e53;
+    let mut index = 0;
+    while index < 128 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut key = state;
+        key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[index / 64][index % 64] = key ^ (key >> 31);
+        index += 1;
+    }
+    keys
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnEntry {
+    key: u64,
+    score: TaperedScore,
+    passed_pawns: BitBoard,
+}
+
+/* Cache of pawn structure evaluation, which only changes with pawn moves and so is mostly
+found in table. Scores are from white's point of view. */
+#[derive(Debug, Clone)]
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl PawnTable {
+    pub fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+    }
+
+    pub fn evaluate(&mut self, board: &Board) -> TaperedScore {
+        /* Pawn structure score, cached terms depend only on pawns, passed pawns are also
+        scored by pieces blocking them and by kings' distance from them. */
+        let key = PawnTable::pawn_key(board);
+        let index = (key % PAWN_TABLE_SIZE as u64) as usize;
+        let mut entry = self.entries[index];
+        // board without pawns has zero key, which matches empty entry with zero score
+        if entry.key != key {
+            entry = PawnTable::evaluate_pawns(board, key);
+            self.entries[index] = entry;
+        }
+
+        let mut score = entry.score;
+        for square in entry.passed_pawns {
+            let color = board.color_on(square).unwrap();
+            let passed_pawn_score = PawnTable::passed_pawn_bonus(board, square, color);
+            match color {
+                Color::White => score += passed_pawn_score,
+                Color::Black => score -= passed_pawn_score,
+            }
+        }
+        score
+    }
+
+    fn pawn_key(board: &Board) -> u64 {
+        let mut key = 0;
+        for color in ALL_COLORS {
+            for square in board.pieces(Piece::Pawn) & board.color_combined(color) {
+                key ^= PAWN_KEYS[color.to_index()][square.to_index()];
+            }
+        }
+        key
+    }
+
+    fn evaluate_pawns(board: &Board, key: u64) -> PawnEntry {
+        let mut entry = PawnEntry {
+            key,
+            ..PawnEntry::default()
+        };
+
+        for color in ALL_COLORS {
+            let own_pawns = board.pieces(Piece::Pawn) & board.color_combined(color);
+            let opponent_pawns = board.pieces(Piece::Pawn) & board.color_combined(!color);
+
+            for square in own_pawns {
+                let (score, passed) =
+                    PawnTable::evaluate_pawn(square, color, own_pawns, opponent_pawns);
+                match color {
+                    Color::White => entry.score += score,
+                    Color::Black => entry.score -= score,
+                }
+                if passed {
+                    entry.passed_pawns |= BitBoard::from_square(square);
+                }
+            }
+        }
+        entry
+    }
+
+    fn evaluate_pawn(
+        square: Square,
+        color: Color,
+        own_pawns: BitBoard,
+        opponent_pawns: BitBoard,
+    ) -> (TaperedScore, bool) {
+        /* Structure score of single pawn and whether it is passed. */
+        let file = get_file(square.get_file());
+        let adjacent_files = get_adjacent_files(square.get_file());
+        let ahead = PawnTable::ranks_ahead(square, color);
+        let mut score = TaperedScore::ZERO;
+
+        // doubled -> every pawn with own pawn in front of it on same file is penalized
+        if own_pawns & file & ahead != EMPTY {
+            score += DOUBLED_PAWN_PENALTY;
+        }
+
+        // isolated -> no own pawn on neighbouring files can ever protect it
+        let isolated = own_pawns & adjacent_files == EMPTY;
+        if isolated {
+            score += ISOLATED_PAWN_PENALTY;
+        }
+
+        // backward -> neighbours are all ahead and advancing is stopped by opponent pawn
+        if !isolated
+            && own_pawns & adjacent_files & !ahead == EMPTY
+            && let Some(stop_square) = square.forward(color)
+            && opponent_pawns & get_pawn_attacks(stop_square, color, !EMPTY) != EMPTY
+        {
+            score += BACKWARD_PAWN_PENALTY;
+        }
+
+        // connected -> protected by own pawn or standing next to one
+        let rank = get_rank(square.get_rank());
+        let protectors = get_pawn_attacks(square, !color, !EMPTY);
+        if own_pawns & (protectors | adjacent_files & rank) != EMPTY {
+            score += CONNECTED_PAWN_BONUS;
+        }
+
+        // passed -> no opponent pawn can stop or capture it on its way
+        let passed = opponent_pawns & (file | adjacent_files) & ahead == EMPTY;
+        if passed {
+            score += PASSED_PAWN_BONUS[PawnTable::relative_rank(square, color)];
+        }
+
+        (score, passed)
+    }
+
+    fn passed_pawn_bonus(board: &Board, square: Square, color: Color) -> TaperedScore {
+        /* Passed pawn with blocked path is worth half, in endgame it is worth more the further
+        opponent's king and the closer own king is to square in front of it. */
+        let rank = PawnTable::relative_rank(square, color);
+        let Some(stop_square) = square.forward(color) else {
+            return TaperedScore::ZERO;
+        };
+
+        let mut bonus = TaperedScore::ZERO;
+        if board.piece_on(stop_square).is_some() {
+            bonus -= PASSED_PAWN_BONUS[rank] / 2;
+        }
+
+        let opponent_king_distance = PawnTable::distance(board.king_square(!color), stop_square);
+        let own_king_distance = PawnTable::distance(board.king_square(color), stop_square);
+        let proximity = PASSED_PAWN_OPPONENT_KING_WEIGHT * opponent_king_distance
+            - PASSED_PAWN_OWN_KING_WEIGHT * own_king_distance;
+        bonus + TaperedScore::new(0, proximity * (rank as i32 - 2).max(0))
+    }
+
+    fn ranks_ahead(square: Square, color: Color) -> BitBoard {
+        /* All squares on ranks in front of square, as seen by given side. */
+        let rank = square.get_rank().to_index();
+        match color {
+            Color::White if rank == 7 => EMPTY,
+            Color::White => BitBoard(!0 << (8 * (rank + 1))),
+            Color::Black => BitBoard((1 << (8 * rank)) - 1),
+        }
+    }
+
+    fn relative_rank(square: Square, color: Color) -> usize {
+        match color {
+            Color::White => square.get_rank().to_index(),
+            Color::Black => 7 - square.get_rank().to_index(),
+        }
+    }
+
+    fn distance(first: Square, second: Square) -> i32 {
+        let ranks = first
+            .get_rank()
+            .to_index()
+            .abs_diff(second.get_rank().to_index());
+        let files = first
+            .get_file()
+            .to_index()
+            .abs_diff(second.get_file().to_index());
+        ranks.max(files) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn evaluate_pawn(fen: &str, square: Square) -> (TaperedScore, bool) {
+        let board = Board::from_str(fen).unwrap();
+        let color = board.color_on(square).unwrap();
+        let pawns = board.pieces(Piece::Pawn);
+        PawnTable::evaluate_pawn(
+            square,
+            color,
+            pawns & board.color_combined(color),
+            pawns & board.color_combined(!color),
+        )
+    }
+
+    fn passed_pawn_bonus(fen: &str, square: Square) -> TaperedScore {
+        let board = Board::from_str(fen).unwrap();
+        PawnTable::passed_pawn_bonus(&board, square, board.color_on(square).unwrap())
+    }
+
+    #[test]
+    fn doubled_and_isolated_pawns() {
+        let fen = "4k3/1p6/8/8/8/P7/P7/4K3 w - - 0 1";
+        assert_eq!(
+            evaluate_pawn(fen, Square::A2),
+            (DOUBLED_PAWN_PENALTY + ISOLATED_PAWN_PENALTY, false)
+        );
+        // only rear pawn counts as doubled
+        assert_eq!(
+            evaluate_pawn(fen, Square::A3),
+            (ISOLATED_PAWN_PENALTY, false)
+        );
+    }
+
+    #[test]
+    fn backward_pawn() {
+        // c2 cannot be protected by d4 and its stop square is attacked by b4
+        let fen = "4k3/8/8/8/1p1P4/8/2P5/4K3 w - - 0 1";
+        assert_eq!(
+            evaluate_pawn(fen, Square::C2),
+            (BACKWARD_PAWN_PENALTY, false)
+        );
+    }
+
+    #[test]
+    fn connected_pawns() {
+        let fen = "4k3/3p4/8/8/3PP3/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            evaluate_pawn(fen, Square::D4),
+            (CONNECTED_PAWN_BONUS, false)
+        );
+        assert_eq!(
+            evaluate_pawn(fen, Square::E4),
+            (CONNECTED_PAWN_BONUS, false)
+        );
+    }
+
+    #[test]
+    fn passed_pawns_of_both_colors() {
+        // pawns have already passed each other, so neither stops the other
+        let fen = "4k3/8/8/3P4/8/4p3/8/K7 w - - 0 1";
+        assert_eq!(
+            evaluate_pawn(fen, Square::D5),
+            (ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[4], true)
+        );
+        assert_eq!(
+            evaluate_pawn(fen, Square::E3),
+            (ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[5], true)
+        );
+
+        // pawn in front stops black pawn, but is not ahead of white pawn
+        let fen = "4k3/8/8/3P4/8/4p3/4P3/K7 w - - 0 1";
+        assert!(!evaluate_pawn(fen, Square::E3).1);
+        assert!(evaluate_pawn(fen, Square::D5).1);
+    }
+
+    #[test]
+    fn blocked_passed_pawn() {
+        // opponent king blocks stop square, own king is six squares away from it
+        let fen = "8/4k3/4P3/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            passed_pawn_bonus(fen, Square::E6),
+            -PASSED_PAWN_BONUS[5] / 2 + TaperedScore::new(0, -6 * 3)
+        );
+    }
+
+    #[test]
+    fn passed_pawn_king_proximity() {
+        // opponent king is four squares from stop square and own king two, scaled by rank
+        let fen = "k7/8/4P3/4K3/8/8/8/8 w - - 0 1";
+        assert_eq!(
+            passed_pawn_bonus(fen, Square::E6),
+            TaperedScore::new(0, (3 * 4 - 2) * 3)
+        );
+
+        // same for black pawn moving down, opponent king is six squares away
+        let fen = "7K/8/8/8/3k4/3p4/8/8 b - - 0 1";
+        assert_eq!(
+            passed_pawn_bonus(fen, Square::D3),
+            TaperedScore::new(0, (3 * 6 - 2) * 3)
+        );
+    }
+
+    #[test]
+    fn clear_drops_cached_entries() {
+        let mut pawn_table = PawnTable::new();
+        pawn_table.evaluate(&Board::default());
+        assert!(pawn_table.entries.iter().any(|entry| entry.key != 0));
+
+        pawn_table.clear();
+        assert!(pawn_table.entries.iter().all(|entry| entry.key == 0));
+    }
+}
@@ -0,0 +1,212 @@
+use std::str::FromStr;
+use std::time::Instant;
+
+use chess::{Board, ChessMove, MoveGen, Piece};
+
+use crate::search_thread::SearchThread;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerftCounts {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+}
+
+impl PerftCounts {
+    fn add(&mut self, other: &PerftCounts) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+    }
+}
+
+struct PerftPosition {
+    fen: &'static str,
+    depth: usize,
+    expected: PerftCounts,
+    // only node count is published for some positions
+    nodes_only: bool,
+}
+
+impl PerftPosition {
+    fn counts(&self) -> PerftCounts {
+        /* Perft of position, limited to node count if only that is published. */
+        let board = Board::from_str(self.fen).unwrap();
+        let counts = perft(&board, self.depth);
+        if self.nodes_only {
+            return PerftCounts {
+                nodes: counts.nodes,
+                ..PerftCounts::default()
+            };
+        }
+        counts
+    }
+}
+
+const fn counts(
+    nodes: u64,
+    captures: u64,
+    en_passants: u64,
+    castles: u64,
+    promotions: u64,
+    checks: u64,
+) -> PerftCounts {
+    PerftCounts {
+        nodes,
+        captures,
+        en_passants,
+        castles,
+        promotions,
+        checks,
+    }
+}
+
+// standard positions with known results, see https://www.chessprogramming.org/Perft_Results
+const PERFT_SUITE: [PerftPosition; 6] = [
+    PerftPosition {
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        depth: 4,
+        expected: counts(197_281, 1_576, 0, 0, 0, 469),
+        nodes_only: false,
+    },
+    PerftPosition {
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        depth: 3,
+        expected: counts(97_862, 17_102, 45, 3_162, 0, 993),
+        nodes_only: false,
+    },
+    PerftPosition {
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        depth: 5,
+        expected: counts(674_624, 52_051, 1_165, 0, 0, 52_950),
+        nodes_only: false,
+    },
+    PerftPosition {
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        depth: 4,
+        expected: counts(422_333, 131_393, 0, 7_795, 60_032, 15_492),
+        nodes_only: false,
+    },
+    PerftPosition {
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        depth: 3,
+        expected: counts(62_379, 0, 0, 0, 0, 0),
+        nodes_only: true,
+    },
+    PerftPosition {
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        depth: 3,
+        expected: counts(89_890, 0, 0, 0, 0, 0),
+        nodes_only: true,
+    },
+];
+
+pub fn perft(board: &Board, depth: usize) -> PerftCounts {
+    /* Count leaf nodes of legal move tree to given depth, with kinds of moves leading to them. */
+    let mut counts = PerftCounts::default();
+    if depth == 0 {
+        counts.nodes = 1;
+        return counts;
+    }
+
+    for chess_move in MoveGen::new_legal(board) {
+        if depth == 1 {
+            counts.add(&leaf_counts(board, chess_move));
+        } else {
+            counts.add(&perft(&board.make_move_new(chess_move), depth - 1));
+        }
+    }
+    counts
+}
+
+pub fn divide(board: &Board, depth: usize) {
+    /* Print leaf node count for each root move, followed by total. */
+    let start = Instant::now();
+    let mut total = PerftCounts::default();
+
+    // root itself is only leaf at depth 0, there are no moves to divide it by
+    if depth == 0 {
+        total = perft(board, depth);
+    } else {
+        for chess_move in MoveGen::new_legal(board) {
+            let counts = perft(&board.make_move_new(chess_move), depth - 1);
+            println!("{}: {}", chess_move, counts.nodes);
+            total.add(&counts);
+        }
+    }
+
+    println!();
+    println!(
+        "Nodes searched: {} ({} ms)",
+        total.nodes,
+        start.elapsed().as_millis()
+    );
+}
+
+pub fn run_suite() {
+    /* Compare counts of standard perft positions with published results. */
+    let mut failed = 0;
+    for position in &PERFT_SUITE {
+        let counts = position.counts();
+
+        let passed = counts == position.expected;
+        if !passed {
+            failed += 1;
+        }
+        println!(
+            "{} depth {} nodes {}: {}",
+            position.fen,
+            position.depth,
+            counts.nodes,
+            if passed { "ok" } else { "FAILED" }
+        );
+        if !passed {
+            println!("  expected {:?}", position.expected);
+            println!("  found    {:?}", counts);
+        }
+    }
+    println!(
+        "perft suite: {} passed, {} failed",
+        PERFT_SUITE.len() - failed,
+        failed
+    );
+}
+
+fn leaf_counts(board: &Board, chess_move: ChessMove) -> PerftCounts {
+    /* Classify last move of perft line the same way search does. */
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let castle = board.piece_on(source) == Some(Piece::King)
+        && source
+            .get_file()
+            .to_index()
+            .abs_diff(dest.get_file().to_index())
+            == 2;
+
+    PerftCounts {
+        nodes: 1,
+        captures: SearchThread::is_capture(board, chess_move) as u64,
+        en_passants: SearchThread::is_en_passant(board, chess_move) as u64,
+        castles: castle as u64,
+        promotions: chess_move.get_promotion().is_some() as u64,
+        checks: (board.make_move_new(chess_move).checkers().popcnt() != 0) as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perft_suite() {
+        for position in &PERFT_SUITE {
+            assert_eq!(position.counts(), position.expected, "{}", position.fen);
+        }
+    }
+}
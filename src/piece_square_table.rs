@@ -0,0 +1,224 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use chess::{ALL_PIECES, ALL_SQUARES, Color, Piece, Square};
+
+use crate::tapered_score::TaperedScore;
+
+// weights of formulas generating default tables
+const PAWN_RANK_WEIGHT: TaperedScore = TaperedScore::new(5, 11);
+const PAWN_FILE_WEIGHT: TaperedScore = TaperedScore::new(5, 2);
+const PAWN_CENTER_WEIGHT: TaperedScore = TaperedScore::new(6, 2);
+const KNIGHT_CENTER_WEIGHT: TaperedScore = TaperedScore::new(7, 5);
+const BISHOP_CENTER_WEIGHT: TaperedScore = TaperedScore::new(5, 4);
+const ROOK_CENTER_WEIGHT: TaperedScore = TaperedScore::new(8, 2);
+const QUEEN_CENTER_WEIGHT: TaperedScore = TaperedScore::new(2, 4);
+// king shelters away from center while there are pieces to attack it
+const KING_CENTER_WEIGHT: TaperedScore = TaperedScore::new(-8, 8);
+
+const PHASE_NAMES: [&str; 2] = ["middlegame", "endgame"];
+
+/* Bonus for piece standing on square, one table per piece with middlegame and endgame values.
+Tables are given from white's point of view, squares of black pieces are mirrored.
+
+File format has for each piece and phase header line with piece and phase name, e.g.
+"knight endgame", followed by eight rows of eight values, from eighth rank down to first,
+empty lines and lines starting with # are ignored. */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceSquareTables {
+    tables: [[TaperedScore; 64]; 6],
+}
+
+impl PieceSquareTables {
+    pub fn default() -> PieceSquareTables {
+        PieceSquareTables::generate()
+    }
+
+    pub fn generate() -> PieceSquareTables {
+        /* Tables from geometric formulas rewarding advanced central pawns and centralized pieces. */
+        let mut tables = [[TaperedScore::ZERO; 64]; 6];
+        for square in ALL_SQUARES {
+            let bonus = |piece: Piece| match piece {
+                Piece::Pawn => PieceSquareTables::pawn_bonus(square),
+                Piece::Knight => PieceSquareTables::center_bonus(square, KNIGHT_CENTER_WEIGHT),
+                Piece::Bishop => PieceSquareTables::center_bonus(square, BISHOP_CENTER_WEIGHT),
+                Piece::Rook => PieceSquareTables::center_file_bonus(square, ROOK_CENTER_WEIGHT),
+                Piece::Queen => PieceSquareTables::center_bonus(square, QUEEN_CENTER_WEIGHT),
+                Piece::King => PieceSquareTables::center_bonus(square, KING_CENTER_WEIGHT),
+            };
+            for piece in ALL_PIECES {
+                tables[piece.to_index()][square.to_index()] = bonus(piece);
+            }
+        }
+        PieceSquareTables { tables }
+    }
+
+    pub fn load(path: &Path) -> Result<PieceSquareTables, String> {
+        let content = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        content.parse()
+    }
+
+    pub fn get(&self, piece: Piece, color: Color, square: Square) -> TaperedScore {
+        let index = match color {
+            Color::White => square.to_index(),
+            Color::Black => square.to_index() ^ 56,
+        };
+        self.tables[piece.to_index()][index]
+    }
+
+    fn pawn_bonus(pawn: Square) -> TaperedScore {
+        // rank bonus -> the further forward the pawn, the more of a bonus
+        let mut bonus = PAWN_RANK_WEIGHT * (pawn.get_rank().to_index() as i32 - 1).abs();
+
+        // file penalty -> central files take none, the closer to rim the less pawn's value
+        let file = pawn.get_file().to_index() as i32;
+        if file < 3 {
+            bonus -= PAWN_FILE_WEIGHT * (3 - file);
+        } else if file > 4 {
+            bonus -= PAWN_FILE_WEIGHT * (file - 4);
+        }
+
+        bonus + PieceSquareTables::center_bonus(pawn, PAWN_CENTER_WEIGHT)
+    }
+
+    fn center_bonus(square: Square, weight: TaperedScore) -> TaperedScore {
+        /* Bonus for occupying squares close to center, only on central files. */
+        let rank = square.get_rank().to_index();
+        if !(3usize..5usize).contains(&square.get_file().to_index()) {
+            return TaperedScore::ZERO;
+        }
+        if (3usize..5usize).contains(&rank) {
+            return weight * 3;
+        }
+        if (2usize..6usize).contains(&rank) {
+            return weight * 2;
+        }
+        if (1usize..7usize).contains(&rank) {
+            return weight;
+        }
+        TaperedScore::ZERO
+    }
+
+    fn center_file_bonus(square: Square, weight: TaperedScore) -> TaperedScore {
+        /* Bonus for occupying files close to center. */
+        let file = square.get_file().to_index();
+        if (3usize..5usize).contains(&file) {
+            weight * 3
+        } else if (2usize..6usize).contains(&file) {
+            weight * 2
+        } else if (1usize..7usize).contains(&file) {
+            weight
+        } else {
+            TaperedScore::ZERO
+        }
+    }
+
+    fn piece_name(piece: Piece) -> &'static str {
+        match piece {
+            Piece::Pawn => "pawn",
+            Piece::Knight => "knight",
+            Piece::Bishop => "bishop",
+            Piece::Rook => "rook",
+            Piece::Queen => "queen",
+            Piece::King => "king",
+        }
+    }
+}
+
+impl FromStr for PieceSquareTables {
+    type Err = String;
+
+    fn from_str(content: &str) -> Result<PieceSquareTables, String> {
+        /* Parse tables in file format, every piece and phase has to be present. */
+        let mut lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let mut tables = [[TaperedScore::ZERO; 64]; 6];
+        let mut found = [[false; 2]; 6];
+
+        while let Some(header) = lines.next() {
+            let words: Vec<&str> = header.split_whitespace().collect();
+            let piece = ALL_PIECES
+                .into_iter()
+                .find(|&piece| words.first() == Some(&PieceSquareTables::piece_name(piece)));
+            let phase = PHASE_NAMES
+                .iter()
+                .position(|&name| words.get(1) == Some(&name));
+            let (Some(piece), Some(phase), 2) = (piece, phase, words.len()) else {
+                return Err(format!("invalid table header \"{}\"", header));
+            };
+
+            for rank in (0..8).rev() {
+                let row = lines
+                    .next()
+                    .ok_or_else(|| format!("missing rows of {} table", header))?;
+                let values = row
+                    .split_whitespace()
+                    .map(str::parse::<i32>)
+                    .collect::<Result<Vec<i32>, _>>()
+                    .map_err(|_| format!("invalid row \"{}\" of {} table", row, header))?;
+                if values.len() != 8 {
+                    return Err(format!(
+                        "row \"{}\" of {} table needs 8 values",
+                        row, header
+                    ));
+                }
+
+                for (file, value) in values.into_iter().enumerate() {
+                    let entry = &mut tables[piece.to_index()][8 * rank + file];
+                    match phase {
+                        0 => entry.middlegame.0 = value,
+                        _ => entry.endgame.0 = value,
+                    }
+                }
+            }
+            found[piece.to_index()][phase] = true;
+        }
+
+        if let Some(piece) = ALL_PIECES
+            .into_iter()
+            .find(|piece| found[piece.to_index()].contains(&false))
+        {
+            return Err(format!(
+                "missing table for {}",
+                PieceSquareTables::piece_name(piece)
+            ));
+        }
+        Ok(PieceSquareTables { tables })
+    }
+}
+
+impl fmt::Display for PieceSquareTables {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        /* Tables in file format, so that generated tables can be saved and edited. */
+        for piece in ALL_PIECES {
+            for (phase, phase_name) in PHASE_NAMES.iter().enumerate() {
+                writeln!(
+                    formatter,
+                    "{} {}",
+                    PieceSquareTables::piece_name(piece),
+                    phase_name
+                )?;
+                for rank in (0..8).rev() {
+                    let row: Vec<String> = (0..8)
+                        .map(|file| {
+                            let entry = self.tables[piece.to_index()][8 * rank + file];
+                            let value = if phase == 0 {
+                                entry.middlegame
+                            } else {
+                                entry.endgame
+                            };
+                            format!("{:4}", value.0)
+                        })
+                        .collect();
+                    writeln!(formatter, "{}", row.join(" "))?;
+                }
+                writeln!(formatter)?;
+            }
+        }
+        Ok(())
+    }
+}
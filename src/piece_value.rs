@@ -1,27 +1,29 @@
 use chess::Piece;
 
+use crate::score::Score;
+
 pub struct PieceValue {
-    pub pawn_value: f64,
-    pub knight_value: f64,
-    pub bishop_value: f64,
-    pub rook_value: f64,
-    pub queen_value: f64,
-    pub king_value: f64,
+    pub pawn_value: Score,
+    pub knight_value: Score,
+    pub bishop_value: Score,
+    pub rook_value: Score,
+    pub queen_value: Score,
+    pub king_value: Score,
 }
 
 impl PieceValue {
     pub fn default() -> PieceValue {
         PieceValue {
-            pawn_value: 100.,
-            knight_value: 350.,
-            bishop_value: 370.,
-            rook_value: 550.,
-            queen_value: 950.,
-            king_value: f64::INFINITY,
+            pawn_value: Score(100),
+            knight_value: Score(350),
+            bishop_value: Score(370),
+            rook_value: Score(550),
+            queen_value: Score(950),
+            king_value: Score::INF,
         }
     }
 
-    pub fn get_piece_value(&self, piece: Piece) -> f64 {
+    pub fn get_piece_value(&self, piece: Piece) -> Score {
         match piece {
             Piece::Pawn => self.pawn_value,
             Piece::Knight => self.knight_value,
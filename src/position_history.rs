@@ -0,0 +1,124 @@
+use chess::{Board, ChessMove, Color, Piece};
+
+#[derive(Debug, Clone)]
+pub struct PositionHistory {
+    hashes: Vec<u64>,
+    halfmove_clocks: Vec<usize>,
+    root: usize,
+}
+
+impl PositionHistory {
+    pub fn new(board: &Board, halfmove_clock: usize) -> PositionHistory {
+        PositionHistory {
+            hashes: vec![board.get_hash()],
+            halfmove_clocks: vec![halfmove_clock],
+            root: 0,
+        }
+    }
+
+    pub fn set_root(&mut self) {
+        /* Mark current position as search root, positions before it are game history. */
+        self.root = self.hashes.len() - 1;
+    }
+
+    pub fn push(&mut self, board: &Board, chess_move: ChessMove, new_board: &Board) {
+        /* Record position after move, pawn moves, captures and castling rights changes
+        are irreversible and reset halfmove clock. */
+        let irreversible = board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            || board.piece_on(chess_move.get_dest()).is_some()
+            || board.castle_rights(Color::White) != new_board.castle_rights(Color::White)
+            || board.castle_rights(Color::Black) != new_board.castle_rights(Color::Black);
+        let halfmove_clock = if irreversible {
+            0
+        } else {
+            self.halfmove_clock() + 1
+        };
+
+        self.hashes.push(new_board.get_hash());
+        self.halfmove_clocks.push(halfmove_clock);
+    }
+
+    pub fn push_null(&mut self, new_board: &Board) {
+        // repetitions are not looked for across null move
+        self.hashes.push(new_board.get_hash());
+        self.halfmove_clocks.push(0);
+    }
+
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+        self.halfmove_clocks.pop();
+    }
+
+    pub fn halfmove_clock(&self) -> usize {
+        *self.halfmove_clocks.last().unwrap()
+    }
+
+    pub fn is_repetition(&self) -> bool {
+        /* Check whether current position already occurred since last irreversible move,
+        once after search root or twice in game history, which is claimable threefold. */
+        let hash = *self.hashes.last().unwrap();
+        let current = self.hashes.len() - 1;
+        let oldest = current.saturating_sub(self.halfmove_clock());
+        let mut repetitions = 0;
+        for index in (oldest..current.saturating_sub(1)).rev().step_by(2) {
+            if self.hashes[index] != hash {
+                continue;
+            }
+            if index > self.root {
+                return true;
+            }
+            repetitions += 1;
+            if repetitions == 2 {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn is_fifty_moves_draw(&self) -> bool {
+        self.halfmove_clock() >= 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn play(history: &mut PositionHistory, board: &mut Board, moves: &str) {
+        for chess_move in moves.split_whitespace() {
+            let chess_move = ChessMove::from_str(chess_move).unwrap();
+            let new_board = board.make_move_new(chess_move);
+            history.push(board, chess_move, &new_board);
+            *board = new_board;
+        }
+    }
+
+    #[test]
+    fn repetition_after_root() {
+        let mut board = Board::default();
+        let mut history = PositionHistory::new(&board, 0);
+        history.set_root();
+        // root itself is part of game history
+        play(&mut history, &mut board, "g1f3 g8f6 f3g1 f6g8");
+        assert!(!history.is_repetition());
+        play(&mut history, &mut board, "g1f3");
+        assert!(history.is_repetition());
+    }
+
+    #[test]
+    fn repetition_before_root() {
+        // single earlier occurrence in game history is not a draw yet
+        let mut board = Board::default();
+        let mut history = PositionHistory::new(&board, 0);
+        play(&mut history, &mut board, "g1f3 g8f6 f3g1 f6g8");
+        history.set_root();
+        play(&mut history, &mut board, "g1f3");
+        assert!(!history.is_repetition());
+
+        // second occurrence makes it threefold repetition
+        play(&mut history, &mut board, "g8f6 f3g1 f6g8");
+        assert!(history.is_repetition());
+    }
+}
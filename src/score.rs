@@ -0,0 +1,108 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+const MAX_MATE_PLY: i32 = 1_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(pub i32);
+
+impl Score {
+    pub const DRAW: Score = Score(0);
+    pub const MATE: Score = Score(12_000);
+    pub const INF: Score = Score(32_000);
+
+    pub fn mate_in(ply: usize) -> Score {
+        /* Value of delivering mate at given ply from root. */
+        Score(Score::MATE.0 - ply as i32)
+    }
+
+    pub fn mated_in(ply: usize) -> Score {
+        -Score::mate_in(ply)
+    }
+
+    pub fn plies_to_mate(self) -> Option<i32> {
+        /* Number of plies until mate if score is a mate score, for either side. */
+        let distance = Score::MATE.0 - self.0.abs();
+        (0..=MAX_MATE_PLY).contains(&distance).then_some(distance)
+    }
+
+    pub fn to_table(self, ply: usize) -> Score {
+        /* Mate scores are stored relative to position, not to root. */
+        match self.plies_to_mate() {
+            Some(_) if self.0 > 0 => Score(self.0 + ply as i32),
+            Some(_) => Score(self.0 - ply as i32),
+            None => self,
+        }
+    }
+
+    pub fn to_search(self, ply: usize) -> Score {
+        match self.plies_to_mate() {
+            Some(_) if self.0 > 0 => Score(self.0 - ply as i32),
+            Some(_) => Score(self.0 + ply as i32),
+            None => self,
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        /* Score in UCI format, mates as number of moves, negative when getting mated. */
+        match self.plies_to_mate() {
+            Some(plies) if self.0 > 0 => write!(formatter, "mate {}", (plies + 1) / 2),
+            Some(plies) => write!(formatter, "mate {}", -plies / 2),
+            None => write!(formatter, "cp {}", self.0),
+        }
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score(self.0 + other.0)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score(self.0 - other.0)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        self.0 -= other.0;
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, factor: i32) -> Score {
+        Score(self.0 * factor)
+    }
+}
+
+impl Div<i32> for Score {
+    type Output = Score;
+
+    fn div(self, divisor: i32) -> Score {
+        Score(self.0 / divisor)
+    }
+}
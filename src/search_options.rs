@@ -1,166 +1,306 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use chess::{Board, ChessMove, Game};
+use chess::{Board, ChessMove, Color, File, MoveGen, Piece, Square};
+
+use crate::depth::Depth;
+use crate::piece_square_table::PieceSquareTables;
+use crate::position_history::PositionHistory;
+use crate::uci_error::UciError;
+
+const GO_PARAMETERS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
 
 #[derive(Debug, Clone)]
 pub struct SearchOptions {
-    pub chess_game: Game,
+    pub board: Board,
+    pub history: PositionHistory,
+    position: Vec<String>,
+    played_moves: Vec<String>,
 
     pub move_time: usize,
     pub white_time: usize,
     pub white_increment: usize,
     pub black_time: usize,
     pub black_increment: usize,
-    pub depth: f64,
+    pub depth: Depth,
+    pub infinite: bool,
+    pub moves_to_go: usize,
+    pub nodes: usize,
+    pub mate: usize,
+    pub search_moves: Vec<ChessMove>,
+    pub pondering: bool,
 
     pub fifty_moves_rule: bool,
-    pub max_depth: f64,
+    pub hash_size: usize,
+    pub late_move_reduction_base: f64,
+    pub late_move_reduction_divisor: f64,
+    pub max_depth: Depth,
     pub move_overhead: f64,
+    pub multi_pv: usize,
+    pub piece_square_tables: PieceSquareTables,
     pub syzygy_path: Option<PathBuf>,
+    pub threads: usize,
 }
 
 impl SearchOptions {
     pub fn default() -> SearchOptions {
         SearchOptions {
-            chess_game: Game::new(),
+            board: Board::default(),
+            history: PositionHistory::new(&Board::default(), 0),
+            position: vec![],
+            played_moves: vec![],
 
             move_time: 0,
             white_time: 0,
             white_increment: 0,
             black_time: 0,
             black_increment: 0,
-            depth: f64::INFINITY,
+            depth: Depth::MAX,
+            infinite: false,
+            moves_to_go: 0,
+            nodes: 0,
+            mate: 0,
+            search_moves: vec![],
+            pondering: false,
 
             fifty_moves_rule: true,
-            max_depth: f64::INFINITY,
+            hash_size: 16,
+            late_move_reduction_base: 0.75,
+            late_move_reduction_divisor: 2.25,
+            max_depth: Depth::MAX,
             move_overhead: 10.,
+            multi_pv: 1,
+            piece_square_tables: PieceSquareTables::default(),
             syzygy_path: None,
+            threads: 1,
         }
     }
 
     pub fn get_uci_options() -> Vec<String> {
         Vec::from([
+            String::from("option name Hash type spin default 16 min 1 max 4096"),
+            String::from("option name Clear Hash type button"),
             String::from("option name MaxDepth type spin default -1 min -1 max 99"),
             String::from("option name Move Overhead type spin default 10 min 0 max 5000"),
+            String::from("option name MultiPV type spin default 1 min 1 max 256"),
+            String::from("option name PieceSquareTables type string default <empty>"),
+            String::from("option name Ponder type check default false"),
             String::from("option name Syzygy50MoveRule type check default true"),
             String::from("option name SyzygyPath type string default <empty>"),
+            String::from("option name Threads type spin default 1 min 1 max 256"),
         ])
     }
 
     pub fn reset(&mut self) {
-        self.chess_game = Game::new();
+        self.board = Board::default();
+        self.history = PositionHistory::new(&self.board, 0);
+        self.position.clear();
+        self.played_moves.clear();
         self.reset_temporary_parameters();
     }
 
-    pub fn set_position(&mut self, args: &[String]) {
-        let mut board = Board::default();
+    pub fn set_position(&mut self, args: &[String]) -> Result<(), UciError> {
+        /* Set position from position command, previous position is kept if command is invalid. */
+        let moves_index = args.iter().position(|r| r == "moves");
+        let position = &args[..moves_index.unwrap_or(args.len())];
+        let played_moves = moves_index.map_or(&args[..0], |index| &args[index + 1..]);
 
-        if args[0] == "fen" {
-            let mut fen = args[1].to_string();
-            for partial in args[2..].as_ref() {
-                if partial == "moves" {
-                    break;
+        // usually previous position is only extended by last moves, so only those are played
+        // on top of kept history of positions for repetition detection, there is no previous
+        // position to extend after reset
+        let known_position = matches!(
+            position.first().map(String::as_str),
+            Some("startpos" | "fen")
+        );
+        let (mut board, mut history, new_moves) = if known_position
+            && position == self.position.as_slice()
+            && played_moves.starts_with(&self.played_moves)
+        {
+            (
+                self.board,
+                self.history.clone(),
+                &played_moves[self.played_moves.len()..],
+            )
+        } else {
+            let (board, halfmove_clock) = match position.first().map(String::as_str) {
+                Some("startpos") => (Board::default(), 0),
+                Some("fen") => {
+                    let fen = position[1..].join(" ");
+                    let board = Board::from_str(&fen).map_err(|_| UciError::InvalidFen(fen))?;
+                    // halfmove clock is not kept by board, it is fifth fen field
+                    let halfmove_clock = position.get(5).and_then(|r| r.parse().ok());
+                    (board, halfmove_clock.unwrap_or(0))
                 }
-                fen += &*String::from(" ");
-                fen += partial;
-            }
-            board = Board::from_str(fen.as_str()).expect("Board could not be created from fen.");
-        }
-
-        let moves_start_index = args
-            .iter()
-            .position(|r| r == "moves")
-            .unwrap_or(args.len() - 1)
-            + 1;
-        let played_moves = args[moves_start_index..].to_vec();
+                _ => return Err(UciError::MissingPosition),
+            };
+            (
+                board,
+                PositionHistory::new(&board, halfmove_clock),
+                played_moves,
+            )
+        };
 
-        let mut game = Game::new_with_board(board);
-        for chess_move in played_moves {
-            game.make_move(ChessMove::from_str(chess_move.as_str()).expect("Invalid move string."));
+        for chess_move in new_moves {
+            let chess_move = SearchOptions::parse_move(&board, chess_move)?;
+            let new_board = board.make_move_new(chess_move);
+            history.push(&board, chess_move, &new_board);
+            board = new_board;
         }
 
-        self.chess_game = game;
+        self.board = board;
+        self.history = history;
+        self.position = position.to_vec();
+        self.played_moves = played_moves.to_vec();
+        Ok(())
     }
 
-    pub fn set_search_parameters(&mut self, args: &[String]) {
-        self.reset_temporary_parameters();
+    pub fn set_search_parameters(&mut self, args: &[String]) -> Result<(), UciError> {
+        /* Set search limits from go command, previous limits are kept if command is invalid. */
+        let mut options = self.clone();
+        options.reset_temporary_parameters();
+        options.pondering = args.iter().any(|r| r == "ponder");
 
-        let infinite_index = args.iter().position(|r| r == "infinite");
-        if infinite_index.is_some() {
-            self.depth = f64::INFINITY;
-            return;
+        // search moves are listed until next parameter, they also restrict infinite analysis
+        if let Some(index) = args.iter().position(|r| r == "searchmoves") {
+            let board = options.board;
+            options.search_moves = args[index + 1..]
+                .iter()
+                .take_while(|r| !GO_PARAMETERS.contains(&r.as_str()))
+                .map(|r| SearchOptions::parse_move(&board, r))
+                .collect::<Result<_, _>>()?;
+            if options.search_moves.is_empty() {
+                return Err(UciError::MissingValue(String::from("searchmoves")));
+            }
         }
 
-        if args.is_empty() {
-            self.depth = 2.;
+        options.infinite = args.iter().any(|r| r == "infinite");
+        if !options.infinite {
+            options.move_time = SearchOptions::parse_parameter(args, "movetime")?.unwrap_or(0);
+            options.white_time = SearchOptions::parse_parameter(args, "wtime")?.unwrap_or(0);
+            options.white_increment = SearchOptions::parse_parameter(args, "winc")?.unwrap_or(0);
+            options.black_time = SearchOptions::parse_parameter(args, "btime")?.unwrap_or(0);
+            options.black_increment = SearchOptions::parse_parameter(args, "binc")?.unwrap_or(0);
+            options.depth = SearchOptions::parse_parameter(args, "depth")?.unwrap_or(Depth::MAX);
+            options.moves_to_go = SearchOptions::parse_parameter(args, "movestogo")?.unwrap_or(0);
+            options.nodes = SearchOptions::parse_parameter(args, "nodes")?.unwrap_or(0);
+            options.mate = SearchOptions::parse_parameter(args, "mate")?.unwrap_or(0);
         }
 
-        let move_time_index = args.iter().position(|r| r == "movetime");
-        let white_time_index = args.iter().position(|r| r == "wtime");
-        let white_increment_index = args.iter().position(|r| r == "winc");
-        let black_time_index = args.iter().position(|r| r == "btime");
-        let black_increment_index = args.iter().position(|r| r == "binc");
-        let depth_index = args.iter().position(|r| r == "depth");
-
-        if move_time_index.is_some() {
-            self.move_time = args[move_time_index.unwrap() + 1].parse().unwrap();
+        // clock of side to move is needed to split time
+        let own_time = match options.board.side_to_move() {
+            Color::White => options.white_time,
+            Color::Black => options.black_time,
+        };
+        let clock_given = options.white_time > 0
+            || options.white_increment > 0
+            || options.black_time > 0
+            || options.black_increment > 0;
+        if options.move_time == 0 && clock_given && own_time == 0 {
+            return Err(UciError::MissingTime);
         }
 
-        if white_time_index.is_some() {
-            self.white_time = args[white_time_index.unwrap() + 1].parse().unwrap();
-        }
-        if white_increment_index.is_some() {
-            self.white_increment = args[white_increment_index.unwrap() + 1].parse().unwrap();
-        }
-        if black_time_index.is_some() {
-            self.black_time = args[black_time_index.unwrap() + 1].parse().unwrap();
-        }
-        if black_increment_index.is_some() {
-            self.black_increment = args[black_increment_index.unwrap() + 1].parse().unwrap();
-        }
-        if depth_index.is_some() {
-            self.depth = args[depth_index.unwrap() + 1].parse().unwrap();
-        }
+        *self = options;
+        Ok(())
     }
 
-    pub fn set_option(&mut self, args: &[String]) {
+    pub fn set_bench_parameters(&mut self, depth: Depth) {
+        /* Bench searches only to fixed depth, without limits left over from previous go command. */
+        self.reset_temporary_parameters();
+        self.depth = depth;
+    }
+
+    pub fn set_option(&mut self, args: &[String]) -> Result<bool, UciError> {
+        /* Set option from setoption command, returns true if engine's hash should be cleared. */
         let name_index = args.iter().position(|r| r == "name");
         let value_index = args.iter().position(|r| r == "value");
 
-        if !name_index.is_some() || !value_index.is_some() {
-            println!("Invalid setoption command.");
-            return;
+        // name has to come before value, name ends where value starts
+        let Some(name_index) =
+            name_index.filter(|&index| value_index.is_none_or(|value_index| index < value_index))
+        else {
+            return Err(UciError::MissingOptionName);
+        };
+
+        // buttons have no value
+        let name_end = value_index.unwrap_or(args.len());
+        let option_name: &str = &args[name_index + 1..name_end].join(" ").to_lowercase();
+        if option_name == "clear hash" {
+            return Ok(true);
         }
 
-        let option_name: &str = &args[name_index.unwrap() + 1..value_index.unwrap()]
-            .join(" ")
-            .to_lowercase();
-        let value = &args[value_index.unwrap() + 1..].join(" ").to_lowercase();
+        let raw_value = match value_index {
+            Some(index) if index + 1 < args.len() => args[index + 1..].join(" "),
+            _ => return Err(UciError::MissingValue(option_name.to_string())),
+        };
+        let value = raw_value.to_lowercase();
 
         match option_name {
+            "hash" => self.hash_size = SearchOptions::parse_spin(option_name, &value, 1, 4096)?,
+            // hidden tuning options, not announced to GUI, values in hundredths
+            "lmrbase" => {
+                self.late_move_reduction_base =
+                    SearchOptions::parse_value::<f64>(option_name, &value)? / 100.
+            }
+            "lmrdivisor" => {
+                self.late_move_reduction_divisor =
+                    SearchOptions::parse_value::<f64>(option_name, &value)? / 100.
+            }
             "maxdepth" => {
-                let depth = value.parse::<f64>().unwrap();
-                if depth == -1. {
-                    self.max_depth = f64::INFINITY;
+                let depth = SearchOptions::parse_spin(option_name, &value, -1, 99)?;
+                if depth == -1 {
+                    self.max_depth = Depth::MAX;
                 } else {
-                    self.max_depth = depth;
+                    self.max_depth = Depth(depth);
                 }
             }
-            "move overhead" => self.move_overhead = value.parse::<f64>().unwrap(),
-            "syzygy50moverule" => self.fifty_moves_rule = value == "true",
+            "move overhead" => {
+                self.move_overhead = SearchOptions::parse_spin(option_name, &value, 0., 5000.)?
+            }
+            "multipv" => self.multi_pv = SearchOptions::parse_spin(option_name, &value, 1, 256)?,
+            // file paths keep their case, empty path restores compiled in tables
+            "piecesquaretables" => {
+                self.piece_square_tables = match value.as_str() {
+                    "<empty>" => PieceSquareTables::default(),
+                    _ => PieceSquareTables::load(Path::new(&raw_value)).map_err(|error| {
+                        UciError::InvalidFile {
+                            path: raw_value.clone(),
+                            error,
+                        }
+                    })?,
+                }
+            }
+            // pondering is started by GUI with go ponder, option only announces it may be used
+            "ponder" => {
+                SearchOptions::parse_check(option_name, &value)?;
+            }
+            "syzygy50moverule" => {
+                self.fifty_moves_rule = SearchOptions::parse_check(option_name, &value)?
+            }
             "syzygypath" => {
                 let path = PathBuf::from(value);
                 self.syzygy_path = if path.exists() { Some(path) } else { None };
             }
-            _ => {}
+            "threads" => self.threads = SearchOptions::parse_spin(option_name, &value, 1, 256)?,
+            _ => return Err(UciError::UnknownOption(option_name.to_string())),
         }
+        Ok(false)
     }
 
-    pub fn search_depth(&self) -> f64 {
-        return [self.max_depth, self.depth]
-            .iter()
-            .fold(f64::INFINITY, |a, &b| a.min(b));
+    pub fn search_depth(&self) -> Depth {
+        self.max_depth.min(self.depth)
     }
 
     fn reset_temporary_parameters(&mut self) {
@@ -169,6 +309,296 @@ impl SearchOptions {
         self.white_increment = 0;
         self.black_time = 0;
         self.black_increment = 0;
-        self.depth = f64::INFINITY;
+        self.depth = Depth::MAX;
+        self.infinite = false;
+        self.moves_to_go = 0;
+        self.nodes = 0;
+        self.mate = 0;
+        self.search_moves = vec![];
+        self.pondering = false;
+    }
+
+    fn parse_move(board: &Board, move_string: &str) -> Result<ChessMove, UciError> {
+        /* Legal move from move string, castling may also be given as king takes rook or O-O. */
+        let king_square = board.king_square(board.side_to_move());
+        let chess_move = match move_string {
+            "O-O" | "0-0" => ChessMove::new(
+                king_square,
+                Square::make_square(king_square.get_rank(), File::G),
+                None,
+            ),
+            "O-O-O" | "0-0-0" => ChessMove::new(
+                king_square,
+                Square::make_square(king_square.get_rank(), File::C),
+                None,
+            ),
+            _ => ChessMove::from_str(move_string)
+                .map_err(|_| UciError::InvalidMove(move_string.to_string()))?,
+        };
+
+        // king takes own rook -> castling to that side
+        let (source, dest) = (chess_move.get_source(), chess_move.get_dest());
+        let chess_move = if source == king_square
+            && board.piece_on(dest) == Some(Piece::Rook)
+            && board.color_on(dest) == Some(board.side_to_move())
+        {
+            let file = if dest.get_file() > source.get_file() {
+                File::G
+            } else {
+                File::C
+            };
+            ChessMove::new(source, Square::make_square(source.get_rank(), file), None)
+        } else {
+            chess_move
+        };
+
+        if !MoveGen::new_legal(board).any(|legal_move| legal_move == chess_move) {
+            return Err(UciError::IllegalMove {
+                chess_move: move_string.to_string(),
+                fen: board.to_string(),
+            });
+        }
+        Ok(chess_move)
+    }
+
+    fn parse_parameter<T: FromStr>(args: &[String], name: &str) -> Result<Option<T>, UciError> {
+        /* Value following parameter name, none if parameter is not present. */
+        let Some(index) = args.iter().position(|r| r == name) else {
+            return Ok(None);
+        };
+        let Some(value) = args.get(index + 1) else {
+            return Err(UciError::MissingValue(name.to_string()));
+        };
+        SearchOptions::parse_value(name, value).map(Some)
+    }
+
+    fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, UciError> {
+        value.parse::<T>().map_err(|_| UciError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    fn parse_spin<T: FromStr + PartialOrd>(
+        name: &str,
+        value: &str,
+        min: T,
+        max: T,
+    ) -> Result<T, UciError> {
+        let number = SearchOptions::parse_value::<T>(name, value)?;
+        if number < min || number > max {
+            return Err(UciError::InvalidValue {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+        Ok(number)
+    }
+
+    fn parse_check(name: &str, value: &str) -> Result<bool, UciError> {
+        match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(UciError::InvalidValue {
+                name: name.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &str) -> Vec<String> {
+        command.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn configured_options() -> SearchOptions {
+        let mut search_options = SearchOptions::default();
+        search_options
+            .set_position(&args("startpos moves e2e4"))
+            .unwrap();
+        search_options
+            .set_search_parameters(&args("wtime 60000 btime 50000 binc 1000"))
+            .unwrap();
+        search_options
+            .set_option(&args("name MaxDepth value 8"))
+            .unwrap();
+        search_options
+    }
+
+    fn assert_unchanged(search_options: &SearchOptions) {
+        /* Invalid command must keep board and limits set by previous commands. */
+        let expected = configured_options();
+        assert_eq!(search_options.board, expected.board);
+        assert_eq!(search_options.position, expected.position);
+        assert_eq!(search_options.played_moves, expected.played_moves);
+        assert_eq!(search_options.white_time, expected.white_time);
+        assert_eq!(search_options.black_time, expected.black_time);
+        assert_eq!(search_options.black_increment, expected.black_increment);
+        assert_eq!(search_options.depth, expected.depth);
+        assert_eq!(search_options.max_depth, expected.max_depth);
+    }
+
+    #[test]
+    fn position_with_invalid_fen() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_position(&args("fen 4k3/8/8/8/8/8/8/4K3 x - - 0 1")),
+            Err(UciError::InvalidFen(String::from(
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1"
+            )))
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn position_with_invalid_moves() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_position(&args("startpos moves e2e4 e7e4")),
+            Err(UciError::IllegalMove {
+                chess_move: String::from("e7e4"),
+                fen: Board::default()
+                    .make_move_new(ChessMove::from_str("e2e4").unwrap())
+                    .to_string(),
+            })
+        );
+        assert_unchanged(&search_options);
+
+        assert_eq!(
+            search_options.set_position(&args("startpos moves e2e4 abc")),
+            Err(UciError::InvalidMove(String::from("abc")))
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn position_without_arguments() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_position(&args("")),
+            Err(UciError::MissingPosition)
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn go_with_missing_time() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_search_parameters(&args("btime 1000 wtime")),
+            Err(UciError::MissingValue(String::from("wtime")))
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn go_with_invalid_time() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_search_parameters(&args("wtime abc btime 1000")),
+            Err(UciError::InvalidValue {
+                name: String::from("wtime"),
+                value: String::from("abc"),
+            })
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn go_with_search_moves() {
+        let mut search_options = configured_options();
+        search_options
+            .set_search_parameters(&args("searchmoves e7e5 d7d5 depth 3"))
+            .unwrap();
+        assert_eq!(
+            search_options.search_moves,
+            vec![
+                ChessMove::from_str("e7e5").unwrap(),
+                ChessMove::from_str("d7d5").unwrap()
+            ]
+        );
+        assert_eq!(search_options.depth, Depth(3));
+    }
+
+    #[test]
+    fn go_with_invalid_search_moves() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_search_parameters(&args("depth 3 searchmoves e2e4")),
+            Err(UciError::IllegalMove {
+                chess_move: String::from("e2e4"),
+                fen: search_options.board.to_string(),
+            })
+        );
+        assert_unchanged(&search_options);
+
+        assert_eq!(
+            search_options.set_search_parameters(&args("searchmoves abc depth 3")),
+            Err(UciError::InvalidMove(String::from("abc")))
+        );
+        assert_unchanged(&search_options);
+
+        assert_eq!(
+            search_options.set_search_parameters(&args("searchmoves depth 3")),
+            Err(UciError::MissingValue(String::from("searchmoves")))
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn setoption_with_value_before_name() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_option(&args("value 5 name Hash")),
+            Err(UciError::MissingOptionName)
+        );
+        assert_eq!(search_options.hash_size, configured_options().hash_size);
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn setoption_with_invalid_value() {
+        let mut search_options = configured_options();
+        assert_eq!(
+            search_options.set_option(&args("name MaxDepth value abc")),
+            Err(UciError::InvalidValue {
+                name: String::from("maxdepth"),
+                value: String::from("abc"),
+            })
+        );
+        assert_unchanged(&search_options);
+    }
+
+    #[test]
+    fn position_without_arguments_after_reset() {
+        let mut search_options = SearchOptions::default();
+        assert_eq!(
+            search_options.set_position(&args("")),
+            Err(UciError::MissingPosition)
+        );
+
+        search_options
+            .set_position(&args("startpos moves e2e4"))
+            .unwrap();
+        search_options.reset();
+        assert_eq!(
+            search_options.set_position(&args("")),
+            Err(UciError::MissingPosition)
+        );
+        assert_eq!(search_options.board, Board::default());
+    }
+
+    #[test]
+    fn position_with_moves_only_after_reset() {
+        let mut search_options = SearchOptions::default();
+        assert_eq!(
+            search_options.set_position(&args("moves e2e4")),
+            Err(UciError::MissingPosition)
+        );
+        assert_eq!(search_options.board, Board::default());
     }
 }
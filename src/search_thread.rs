@@ -0,0 +1,978 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece};
+
+use crate::depth::Depth;
+use crate::heuristic::Heuristic;
+use crate::piece_value::PieceValue;
+use crate::position_history::PositionHistory;
+use crate::score::Score;
+use crate::search_options::SearchOptions;
+use crate::static_exchange::static_exchange_evaluation;
+use crate::transposition_table::{Bound, TableEntry, TranspositionTable};
+
+const NULL_MOVE_REDUCTION: Depth = Depth(2);
+const NULL_MOVE_VERIFICATION_DEPTH: Depth = Depth(6);
+const LATE_MOVE_REDUCTION_MIN_DEPTH: Depth = Depth(3);
+const LATE_MOVE_REDUCTION_FULL_DEPTH_MOVES: usize = 3;
+const LATE_MOVE_REDUCTION_TABLE_SIZE: usize = 64;
+const ASPIRATION_MIN_DEPTH: Depth = Depth(5);
+const ASPIRATION_WINDOW: Score = Score(25);
+const ASPIRATION_MAX_WINDOW: Score = Score(500);
+const NODES_REPORT_INTERVAL: usize = 1024;
+const MAX_PLY: usize = 128;
+const CAPTURE_SCORE: i32 = 1 << 28;
+const KILLER_SCORE: i32 = 1 << 27;
+const MAX_HISTORY_SCORE: i32 = 1 << 26;
+
+pub struct SearchThread {
+    id: usize,
+    heuristic: Heuristic,
+    transposition_table: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    nodes: Arc<AtomicUsize>,
+    pondering: Arc<AtomicBool>,
+    unreported_nodes: usize,
+    node_limit: usize,
+    mate_limit: usize,
+    infinite: bool,
+    search_moves: Vec<ChessMove>,
+    position_history: PositionHistory,
+    timer: Option<Instant>,
+    time_for_move: f64,
+    late_move_reductions: [[i32; LATE_MOVE_REDUCTION_TABLE_SIZE]; LATE_MOVE_REDUCTION_TABLE_SIZE],
+    killer_moves: [[Option<ChessMove>; 2]; MAX_PLY],
+    history: [[[i32; 64]; 64]; 2],
+    multi_pv: usize,
+    excluded_root_moves: Vec<ChessMove>,
+}
+
+impl SearchThread {
+    pub fn new(
+        id: usize,
+        transposition_table: Arc<TranspositionTable>,
+        stop: Arc<AtomicBool>,
+        nodes: Arc<AtomicUsize>,
+        pondering: Arc<AtomicBool>,
+    ) -> SearchThread {
+        SearchThread {
+            id,
+            heuristic: Heuristic::default(),
+            transposition_table,
+            stop,
+            nodes,
+            pondering,
+            unreported_nodes: 0,
+            node_limit: 0,
+            mate_limit: 0,
+            infinite: false,
+            search_moves: vec![],
+            position_history: PositionHistory::new(&Board::default(), 0),
+            timer: None,
+            time_for_move: f64::INFINITY,
+            late_move_reductions: [[0; LATE_MOVE_REDUCTION_TABLE_SIZE];
+                LATE_MOVE_REDUCTION_TABLE_SIZE],
+            killer_moves: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+            multi_pv: 1,
+            excluded_root_moves: vec![],
+        }
+    }
+
+    pub fn initialize(
+        &mut self,
+        search_options: &SearchOptions,
+        transposition_table: &Arc<TranspositionTable>,
+    ) {
+        self.transposition_table = Arc::clone(transposition_table);
+        self.multi_pv = search_options.multi_pv;
+        self.node_limit = search_options.nodes;
+        self.mate_limit = search_options.mate;
+        self.infinite = search_options.infinite;
+        self.search_moves = search_options.search_moves.clone();
+        self.unreported_nodes = 0;
+        self.initialize_heuristic(search_options);
+        self.initialize_late_move_reductions(search_options);
+        self.age_move_ordering();
+        self.start_timer(search_options);
+    }
+
+    pub fn reset(&mut self) {
+        /* Drop move ordering state and cached pawn evaluation kept between searches,
+        so that games are independent. */
+        self.killer_moves = [[None; 2]; MAX_PLY];
+        self.history = [[[0; 64]; 64]; 2];
+        self.heuristic.clear_pawn_table();
+    }
+
+    fn is_main(&self) -> bool {
+        self.id == 0
+    }
+
+    fn age_move_ordering(&mut self) {
+        /* Forget killer moves and halve history scores, so that older searches weigh less. */
+        self.killer_moves = [[None; 2]; MAX_PLY];
+        for score in self.history.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    fn initialize_heuristic(&mut self, search_options: &SearchOptions) {
+        self.heuristic.fifty_moves_rule = search_options.fifty_moves_rule;
+        self.heuristic.syzygy_path = search_options.syzygy_path.clone();
+        self.heuristic.piece_square_tables = search_options.piece_square_tables.clone();
+    }
+
+    fn initialize_late_move_reductions(&mut self, search_options: &SearchOptions) {
+        /* Fill reduction table indexed by remaining depth and move number. */
+        for (depth, reductions) in self.late_move_reductions.iter_mut().enumerate() {
+            for (move_number, reduction) in reductions.iter_mut().enumerate() {
+                *reduction = if depth == 0 || move_number == 0 {
+                    0
+                } else {
+                    (search_options.late_move_reduction_base
+                        + (depth as f64).ln() * (move_number as f64).ln()
+                            / search_options.late_move_reduction_divisor)
+                        .floor()
+                        .max(0.) as i32
+                };
+            }
+        }
+    }
+
+    fn count_node(&mut self) {
+        /* Count searched node locally, shared counter is only updated once in a while. */
+        self.unreported_nodes += 1;
+        if self.unreported_nodes >= NODES_REPORT_INTERVAL {
+            self.flush_nodes();
+        }
+    }
+
+    fn flush_nodes(&mut self) {
+        self.nodes
+            .fetch_add(self.unreported_nodes, Ordering::Relaxed);
+        self.unreported_nodes = 0;
+    }
+
+    fn check_stop(&mut self) -> bool {
+        /* Helper threads only follow stop flag, time and node limits are kept by main thread.
+        While pondering there is no time limit, timer starts with ponder hit. */
+        if self.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        if !self.is_main() {
+            return false;
+        }
+        if self.node_limit > 0
+            && self.nodes.load(Ordering::Relaxed) + self.unreported_nodes >= self.node_limit
+        {
+            return true;
+        }
+        if self.pondering.load(Ordering::Relaxed) {
+            return false;
+        }
+        let timer = *self.timer.get_or_insert_with(Instant::now);
+        timer.elapsed().as_millis() as f64 > self.time_for_move
+    }
+
+    pub fn search(
+        &mut self,
+        board: &Board,
+        history: &PositionHistory,
+        max_depth: Depth,
+    ) -> Option<ChessMove> {
+        /* Iterative deepening from root, returns best move, none if root has no legal move. */
+        let start = Instant::now();
+        self.position_history = history.clone();
+        self.position_history.set_root();
+
+        // start with best ordered move, to be used in case of timeout before first depth is reached,
+        // there is none if root is checkmate or stalemate
+        let move_gen = MoveGen::new_legal(board);
+        let possible_moves: Vec<_> = move_gen
+            .filter(|chess_move| self.is_root_move_allowed(chess_move))
+            .collect();
+        let mut moves: Vec<ChessMove> = self
+            .fallback_move(board, &possible_moves)
+            .into_iter()
+            .collect();
+
+        // odd-numbered helpers start one depth later, so that threads do not search in lockstep
+        let mut depth = if self.id % 2 == 1 {
+            Depth::ONE
+        } else {
+            Depth::ZERO
+        };
+        let mut evaluation = Score::DRAW;
+
+        // multiple lines are searched only by main thread, helpers just fill transposition table
+        let multi_pv = if self.is_main() {
+            self.multi_pv.clamp(1, possible_moves.len().max(1))
+        } else {
+            1
+        };
+        let mut evaluations: Vec<Score> = vec![Score::DRAW; multi_pv];
+
+        'iterative_deepening: while depth < max_depth && !possible_moves.is_empty() {
+            depth += Depth::ONE;
+            self.excluded_root_moves.clear();
+
+            // each next line is searched with better lines' first moves excluded from root
+            for (pv_index, previous_evaluation) in evaluations.iter_mut().enumerate() {
+                let Ok((eval, pv)) =
+                    self.search_root(board, depth, *previous_evaluation, pv_index + 1, start)
+                else {
+                    break 'iterative_deepening;
+                };
+                let Some(&first_move) = pv.first() else {
+                    break;
+                };
+
+                *previous_evaluation = eval;
+                self.excluded_root_moves.push(first_move);
+                if pv_index == 0 {
+                    evaluation = eval;
+                    moves = pv.clone();
+                }
+
+                if !self.is_main() {
+                    continue;
+                }
+
+                let nodes_searched = self.nodes.load(Ordering::Relaxed);
+                let mut string_moves: Vec<String> = vec![];
+                for chess_move in &pv {
+                    string_moves.push(chess_move.to_string());
+                }
+
+                println!(
+                    "info multipv {} depth {} score {} nodes {} nps {} time {} pv {}",
+                    pv_index + 1,
+                    depth,
+                    eval,
+                    nodes_searched,
+                    (1_000_000. * nodes_searched as f64 / start.elapsed().as_micros() as f64)
+                        as usize,
+                    start.elapsed().as_millis(),
+                    string_moves.join(" ")
+                );
+            }
+
+            // no need to search deeper once forced mate has been fully seen
+            if evaluation
+                .plies_to_mate()
+                .is_some_and(|plies| plies <= depth.0)
+            {
+                break;
+            }
+            // mate search is over once mate in requested number of moves is found
+            if self.mate_limit > 0
+                && evaluation > Score::DRAW
+                && evaluation
+                    .plies_to_mate()
+                    .is_some_and(|plies| plies < 2 * self.mate_limit as i32)
+            {
+                break;
+            }
+        }
+
+        if self.is_main() {
+            // best move must not be reported before ponder hit or stop, even if search is finished
+            while (self.infinite || self.pondering.load(Ordering::Relaxed))
+                && !self.stop.load(Ordering::Relaxed)
+            {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            // helper threads are not needed once main thread has decided
+            self.stop.store(true, Ordering::Relaxed);
+            // null move is reported when there is no legal move at root
+            match (moves.first(), self.ponder_move(board, &moves)) {
+                (Some(best_move), Some(ponder_move)) => {
+                    println!("bestmove {} ponder {}", best_move, ponder_move)
+                }
+                (Some(best_move), None) => println!("bestmove {}", best_move),
+                (None, _) => println!("bestmove 0000"),
+            }
+        }
+        moves.first().copied()
+    }
+
+    fn is_root_move_allowed(&self, chess_move: &ChessMove) -> bool {
+        self.search_moves.is_empty() || self.search_moves.contains(chess_move)
+    }
+
+    fn fallback_move(&self, board: &Board, possible_moves: &[ChessMove]) -> Option<ChessMove> {
+        /* First move in search order, hash move from previous search if there is one. */
+        let hash_move = self
+            .transposition_table
+            .probe(board.get_hash())
+            .and_then(|entry| entry.best_move);
+        self.order_moves(
+            board,
+            possible_moves.to_vec(),
+            hash_move,
+            &self.killer_moves(0),
+        )
+        .first()
+        .copied()
+    }
+
+    fn ponder_move(&self, board: &Board, moves: &[ChessMove]) -> Option<ChessMove> {
+        /* Expected reply to best move, taken from principal variation or from hash move. */
+        if let Some(&ponder_move) = moves.get(1) {
+            return Some(ponder_move);
+        }
+
+        let board = board.make_move_new(*moves.first()?);
+        self.transposition_table
+            .probe(board.get_hash())
+            .and_then(|entry| entry.best_move)
+            .filter(|&chess_move| board.legal(chess_move))
+    }
+
+    fn search_root(
+        &mut self,
+        board: &Board,
+        depth: Depth,
+        previous_evaluation: Score,
+        pv_number: usize,
+        start: Instant,
+    ) -> Result<(Score, Vec<ChessMove>), &'static str> {
+        /* Search root to given depth, starting from aspiration window around previous score
+        and widening it on the failing side until score falls inside. */
+        let mut window = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = (-Score::INF, Score::INF);
+        if depth >= ASPIRATION_MIN_DEPTH {
+            (alpha, beta) = (previous_evaluation - window, previous_evaluation + window);
+        }
+
+        loop {
+            let result = self.negamax(board, depth, 0, alpha, beta, true);
+            self.flush_nodes();
+            let (eval, pv) = result?;
+            let nodes_searched = self.nodes.load(Ordering::Relaxed);
+
+            let bound = if eval <= alpha && alpha > -Score::INF {
+                window = window * 2;
+                alpha = if window > ASPIRATION_MAX_WINDOW {
+                    -Score::INF
+                } else {
+                    eval - window
+                };
+                "upperbound"
+            } else if eval >= beta && beta < Score::INF {
+                window = window * 2;
+                beta = if window > ASPIRATION_MAX_WINDOW {
+                    Score::INF
+                } else {
+                    eval + window
+                };
+                "lowerbound"
+            } else {
+                return Ok((eval, pv));
+            };
+
+            if self.is_main() {
+                println!(
+                    "info multipv {} depth {} score {} {} nodes {} time {}",
+                    pv_number,
+                    depth,
+                    eval,
+                    bound,
+                    nodes_searched,
+                    start.elapsed().as_millis(),
+                );
+            }
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        board: &Board,
+        mut depth: Depth,
+        ply: usize,
+        mut alpha: Score,
+        mut beta: Score,
+        null_move_allowed: bool,
+    ) -> Result<(Score, Vec<ChessMove>), &'static str> {
+        if self.check_stop() {
+            return Err("Calculation stopped.");
+        }
+
+        self.count_node();
+
+        let status = board.status();
+        if status != BoardStatus::Ongoing {
+            return Ok((self.heuristic.evaluate_result(status, ply), vec![]));
+        }
+        if ply > 0
+            && (self.position_history.is_repetition()
+                || self.position_history.is_fifty_moves_draw())
+        {
+            return Ok((Score::DRAW, vec![]));
+        }
+
+        // mate distance pruning -> no line can be better than mating on next move,
+        // or worse than getting mated right here
+        if ply > 0 {
+            alpha = alpha.max(Score::mated_in(ply));
+            beta = beta.min(Score::mate_in(ply + 1));
+            if alpha >= beta {
+                return Ok((alpha, vec![]));
+            }
+        }
+
+        // check extension -> positions in check are searched one ply deeper
+        let in_check = board.checkers().popcnt() != 0;
+        if in_check {
+            depth += Depth::ONE;
+        }
+
+        if depth <= Depth::ZERO {
+            let evaluation = self.quiescence(board, alpha, beta, ply, 0)?;
+            return Ok((evaluation, vec![]));
+        }
+
+        let hash = board.get_hash();
+        let mut hash_move: Option<ChessMove> = None;
+
+        // transposition table cutoffs, never at root so that a full line is always returned
+        if let Some(entry) = self.transposition_table.probe(hash) {
+            hash_move = entry.best_move;
+            let score = entry.score.to_search(ply);
+            if ply > 0 && entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => {
+                        return Ok((score, hash_move.into_iter().collect()));
+                    }
+                    Bound::Lower if score >= beta => {
+                        return Ok((beta, vec![]));
+                    }
+                    Bound::Upper if score <= alpha => {
+                        return Ok((alpha, vec![]));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // null move pruning -> if passing the turn still beats beta, a real move would too,
+        // not used in check, in pawn endings prone to zugzwang and right after another null move
+        if ply > 0
+            && null_move_allowed
+            && beta < Score::INF
+            && depth > NULL_MOVE_REDUCTION
+            && !in_check
+            && SearchThread::has_non_pawn_material(board)
+            && let Some(null_board) = board.null_move()
+        {
+            let reduced_depth = depth - Depth::ONE - NULL_MOVE_REDUCTION;
+            self.position_history.push_null(&null_board);
+            let result = self.negamax(
+                &null_board,
+                reduced_depth,
+                ply + 1,
+                -beta,
+                -beta + Score(1),
+                false,
+            );
+            self.position_history.pop();
+            let (eval, _) = result?;
+
+            if -eval >= beta {
+                if depth < NULL_MOVE_VERIFICATION_DEPTH {
+                    return Ok((beta, vec![]));
+                }
+
+                // verify cutoff with reduced search without null moves to detect zugzwang
+                let (eval, _) = self.negamax(
+                    board,
+                    depth - NULL_MOVE_REDUCTION,
+                    ply,
+                    beta - Score(1),
+                    beta,
+                    false,
+                )?;
+                if eval >= beta {
+                    return Ok((beta, vec![]));
+                }
+            }
+        }
+
+        let legal_moves = MoveGen::new_legal(board).collect();
+        let killer_moves = self.killer_moves(ply);
+        let mut ordered_moves = self.order_moves(board, legal_moves, hash_move, &killer_moves);
+        let mut best_moves: Vec<ChessMove> = vec![];
+        let mut moves: Vec<ChessMove>;
+        let mut evaluation: Score;
+        let mut bound = Bound::Upper;
+
+        // root moves outside searchmoves or already reported on better multi pv lines are skipped,
+        // such partial root search must not be stored in transposition table
+        let excluding_moves =
+            ply == 0 && (!self.search_moves.is_empty() || !self.excluded_root_moves.is_empty());
+        if excluding_moves {
+            ordered_moves.retain(|chess_move| {
+                self.is_root_move_allowed(chess_move)
+                    && !self.excluded_root_moves.contains(chess_move)
+            });
+        }
+
+        for (move_number, chess_move) in ordered_moves.into_iter().enumerate() {
+            let new_board = board.make_move_new(chess_move);
+            self.position_history.push(board, chess_move, &new_board);
+
+            // late move reductions -> quiet moves late in move ordering are rarely best
+            let gives_check = new_board.checkers().popcnt() != 0;
+            let reduction = if move_number >= LATE_MOVE_REDUCTION_FULL_DEPTH_MOVES
+                && depth >= LATE_MOVE_REDUCTION_MIN_DEPTH
+                && !in_check
+                && !gives_check
+                && !killer_moves.contains(&Some(chess_move))
+                && SearchThread::is_quiet(board, chess_move)
+            {
+                self.late_move_reduction(depth, move_number)
+            } else {
+                Depth::ZERO
+            };
+
+            // principal variation search -> first move gets full window, the rest are expected
+            // to be worse, which is proven by null window search and re-searched if it fails high
+            let mut result = if move_number == 0 {
+                self.negamax(&new_board, depth - Depth::ONE, ply + 1, -beta, -alpha, true)
+            } else {
+                self.negamax(
+                    &new_board,
+                    depth - Depth::ONE - reduction,
+                    ply + 1,
+                    -alpha - Score(1),
+                    -alpha,
+                    true,
+                )
+            };
+            if reduction > Depth::ZERO
+                && let Ok((eval, _)) = &result
+                && -*eval > alpha
+            {
+                result = self.negamax(
+                    &new_board,
+                    depth - Depth::ONE,
+                    ply + 1,
+                    -alpha - Score(1),
+                    -alpha,
+                    true,
+                );
+            }
+            if move_number > 0
+                && let Ok((eval, _)) = &result
+                && -*eval > alpha
+                && -*eval < beta
+            {
+                result = self.negamax(&new_board, depth - Depth::ONE, ply + 1, -beta, -alpha, true);
+            }
+            self.position_history.pop();
+
+            match result {
+                Ok((eval, pv)) => {
+                    evaluation = eval;
+                    moves = pv;
+                }
+                Err(message) => return Err(message),
+            }
+
+            evaluation = -evaluation;
+            moves.insert(0, chess_move);
+
+            if evaluation >= beta {
+                if SearchThread::is_quiet(board, chess_move) {
+                    self.update_move_ordering(board, chess_move, depth, ply);
+                }
+                if !excluding_moves {
+                    self.transposition_table.store(TableEntry {
+                        key: hash,
+                        depth,
+                        bound: Bound::Lower,
+                        score: beta.to_table(ply),
+                        best_move: Some(chess_move),
+                    });
+                }
+                return Ok((beta, vec![]));
+            }
+            if evaluation > alpha {
+                alpha = evaluation;
+                best_moves = moves;
+                bound = Bound::Exact;
+            }
+        }
+
+        if !excluding_moves {
+            self.transposition_table.store(TableEntry {
+                key: hash,
+                depth,
+                bound,
+                score: alpha.to_table(ply),
+                best_move: best_moves.first().copied().or(hash_move),
+            });
+        }
+
+        Ok((alpha, best_moves))
+    }
+
+    fn quiescence(
+        &mut self,
+        board: &Board,
+        mut alpha: Score,
+        beta: Score,
+        ply: usize,
+        quiescence_ply: usize,
+    ) -> Result<Score, &'static str> {
+        if self.check_stop() {
+            return Err("Calculation stopped.");
+        }
+
+        // first quiescence node is the same position as depth 0 negamax node
+        if quiescence_ply > 0 {
+            self.count_node();
+        }
+
+        let status = board.status();
+        if status != BoardStatus::Ongoing {
+            return Ok(self.heuristic.evaluate_result(status, ply));
+        }
+        if quiescence_ply > 0
+            && (self.position_history.is_repetition()
+                || self.position_history.is_fifty_moves_draw())
+        {
+            return Ok(Score::DRAW);
+        }
+
+        let in_check = board.checkers().popcnt() != 0;
+        let evaluation = self.heuristic.evaluate_position(board) * 95 / 100;
+        let use_delta_pruning = board.combined().popcnt() > 8;
+        let piece_value = PieceValue::default();
+
+        // side in check cannot stand pat, all evasions have to be searched
+        let moves = if in_check {
+            let legal_moves = MoveGen::new_legal(board).collect();
+            self.order_moves(board, legal_moves, None, &[])
+                .into_iter()
+                .map(|chess_move| (chess_move, false, false))
+                .collect()
+        } else {
+            if evaluation >= beta {
+                return Ok(beta);
+            }
+            if use_delta_pruning && evaluation < alpha - piece_value.queen_value {
+                return Ok(alpha);
+            }
+            if evaluation > alpha {
+                alpha = evaluation;
+            }
+
+            // checks only on first quiescence ply, deeper only captures are searched
+            self.get_captures_and_checks(board, quiescence_ply == 0)
+        };
+
+        for (chess_move, is_capture, is_en_passant) in moves {
+            if is_capture || is_en_passant {
+                // losing captures are not worth searching
+                let exchange = static_exchange_evaluation(board, chess_move);
+                if exchange < Score::DRAW {
+                    continue;
+                }
+                // delta pruning -> even winning the exchange does not get evaluation near alpha
+                if use_delta_pruning && evaluation + exchange + piece_value.pawn_value < alpha {
+                    continue;
+                }
+            }
+
+            let new_board = board.make_move_new(chess_move);
+            self.position_history.push(board, chess_move, &new_board);
+            let result = self.quiescence(&new_board, -beta, -alpha, ply + 1, quiescence_ply + 1);
+            self.position_history.pop();
+            let score = -result?;
+
+            if score >= beta {
+                return Ok(beta);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        Ok(alpha)
+    }
+
+    fn killer_moves(&self, ply: usize) -> [Option<ChessMove>; 2] {
+        self.killer_moves.get(ply).copied().unwrap_or([None; 2])
+    }
+
+    fn update_move_ordering(
+        &mut self,
+        board: &Board,
+        chess_move: ChessMove,
+        depth: Depth,
+        ply: usize,
+    ) {
+        /* Remember quiet move causing beta cutoff as killer and reward it in history. */
+        if let Some(killers) = self.killer_moves.get_mut(ply)
+            && killers[0] != Some(chess_move)
+        {
+            killers[1] = killers[0];
+            killers[0] = Some(chess_move);
+        }
+
+        let score = &mut self.history[board.side_to_move().to_index()]
+            [chess_move.get_source().to_index()][chess_move.get_dest().to_index()];
+        *score = (*score + depth.0 * depth.0).min(MAX_HISTORY_SCORE);
+    }
+
+    fn late_move_reduction(&self, depth: Depth, move_number: usize) -> Depth {
+        /* Reduction for move, never dropping search straight into quiescence. */
+        let depth_index = (depth.0 as usize).min(LATE_MOVE_REDUCTION_TABLE_SIZE - 1);
+        let move_index = move_number.min(LATE_MOVE_REDUCTION_TABLE_SIZE - 1);
+        Depth(self.late_move_reductions[depth_index][move_index].min(depth.0 - 2))
+    }
+
+    fn is_quiet(board: &Board, chess_move: ChessMove) -> bool {
+        /* Check whether move is neither capture (including en passant) nor promotion. */
+        !SearchThread::is_capture(board, chess_move) && chess_move.get_promotion().is_none()
+    }
+
+    pub fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+        board.piece_on(chess_move.get_dest()).is_some()
+            || SearchThread::is_en_passant(board, chess_move)
+    }
+
+    pub fn is_en_passant(board: &Board, chess_move: ChessMove) -> bool {
+        /* Pawn moving diagonally to empty square can only capture en passant. */
+        board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file()
+            && board.piece_on(chess_move.get_dest()).is_none()
+    }
+
+    fn has_non_pawn_material(board: &Board) -> bool {
+        /* Check whether side to move has any pieces besides king and pawns. */
+        let pawns_and_king = board.pieces(Piece::Pawn) | board.pieces(Piece::King);
+        (board.color_combined(board.side_to_move()) & !pawns_and_king).popcnt() > 0
+    }
+
+    fn get_captures_and_checks(
+        &self,
+        board: &Board,
+        include_checks: bool,
+    ) -> Vec<(ChessMove, bool, bool)> {
+        let mut captures_and_checks: Vec<(ChessMove, bool, bool)> = vec![];
+        let legal_moves = MoveGen::new_legal(board).collect();
+        let ordered_moves = self.order_moves(board, legal_moves, None, &[]);
+
+        for chess_move in ordered_moves {
+            let board_after_move = board.make_move_new(chess_move);
+
+            let captured_piece = board.piece_on(chess_move.get_dest()).is_some();
+            let is_check = board_after_move.checkers().popcnt() != 0;
+
+            let en_passant_capture = SearchThread::is_en_passant(board, chess_move);
+
+            if captured_piece || en_passant_capture || (include_checks && is_check) {
+                captures_and_checks.push((chess_move, captured_piece, en_passant_capture));
+            }
+        }
+
+        captures_and_checks
+    }
+
+    fn start_timer(&mut self, search_options: &SearchOptions) {
+        /* Start timer to check elapsed time and stop it over limit, pondering search starts it later. */
+        self.timer = (!search_options.pondering).then(Instant::now);
+        self.time_for_move = f64::INFINITY;
+
+        match (
+            search_options.board.side_to_move(),
+            search_options.move_time,
+            search_options.white_time,
+            search_options.white_increment,
+            search_options.black_time,
+            search_options.black_increment,
+        ) {
+            (_, 0, 0, 0, 0, 0) => {}
+            (_, move_time, _, _, _, _) if move_time > 0 => {
+                self.time_for_move = move_time as f64;
+            }
+            (Color::White, _, white_time, white_increment, _, _)
+                if white_time > 0 && search_options.moves_to_go > 0 =>
+            {
+                self.time_for_move = (white_time as f64 / (search_options.moves_to_go + 1) as f64
+                    + white_increment as f64
+                    - search_options.move_overhead)
+                    .min(white_time as f64 - search_options.move_overhead);
+            }
+            (Color::White, _, white_time, 0, _, _) if white_time > 0 => {
+                self.time_for_move = 0.05 * (white_time as f64 - search_options.move_overhead);
+            }
+            (Color::White, _, white_time, white_increment, _, _) if white_time > 0 => {
+                self.time_for_move = (0.1 * white_time as f64 + white_increment as f64
+                    - search_options.move_overhead)
+                    .min(white_time as f64 - search_options.move_overhead);
+            }
+            (Color::Black, _, _, _, black_time, black_increment)
+                if black_time > 0 && search_options.moves_to_go > 0 =>
+            {
+                self.time_for_move = (black_time as f64 / (search_options.moves_to_go + 1) as f64
+                    + black_increment as f64
+                    - search_options.move_overhead)
+                    .min(black_time as f64 - search_options.move_overhead);
+            }
+            (Color::Black, _, _, _, black_time, 0) if black_time > 0 => {
+                self.time_for_move = 0.05 * (black_time as f64 - search_options.move_overhead);
+            }
+            (Color::Black, _, _, _, black_time, black_increment) if black_time > 0 => {
+                self.time_for_move = (0.1 * black_time as f64 + black_increment as f64
+                    - search_options.move_overhead)
+                    .min(black_time as f64 - search_options.move_overhead);
+            }
+            _ => panic!("Incorrect time options."),
+        }
+    }
+
+    fn order_moves(
+        &self,
+        board: &Board,
+        moves: Vec<ChessMove>,
+        hash_move: Option<ChessMove>,
+        killer_moves: &[Option<ChessMove>],
+    ) -> Vec<ChessMove> {
+        let mut scored_moves: Vec<(ChessMove, i32)> = vec![];
+        let piece_value = PieceValue::default();
+
+        for mv in moves {
+            // Hash move from transposition table is searched first
+            if Some(mv) == hash_move {
+                scored_moves.push((mv, i32::MAX));
+                continue;
+            }
+
+            let mut score = 0;
+            let from = mv.get_source();
+            let to = mv.get_dest();
+            let attacker = board.piece_on(from);
+            let victim = board.piece_on(to);
+
+            // MVV-LVA scoring
+            if let (Some(att), Some(vic)) = (attacker, victim) {
+                score +=
+                    10 * piece_value.get_piece_value(vic).0 - piece_value.get_piece_value(att).0;
+            }
+
+            // Promotion bonus
+            if let Some(promo) = mv.get_promotion() {
+                score += 5 * piece_value.get_piece_value(promo).0;
+            }
+
+            // Captures and promotions first, then killer moves, then quiet moves by history,
+            // captures losing material in static exchange are left for the end
+            if !SearchThread::is_quiet(board, mv) {
+                let losing_capture = victim.is_some_and(|vic| {
+                    piece_value.get_piece_value(vic)
+                        < piece_value.get_piece_value(attacker.unwrap())
+                }) && static_exchange_evaluation(board, mv) < Score::DRAW;
+                score += if losing_capture {
+                    -CAPTURE_SCORE
+                } else {
+                    CAPTURE_SCORE
+                };
+            } else if let Some(index) = killer_moves.iter().position(|&k| k == Some(mv)) {
+                score += KILLER_SCORE - index as i32;
+            } else {
+                score +=
+                    self.history[board.side_to_move().to_index()][from.to_index()][to.to_index()];
+            }
+
+            // Check bonus
+            let new_board = board.make_move_new(mv);
+            if new_board.checkers().0 != 0 {
+                score += 1;
+            }
+
+            scored_moves.push((mv, score));
+        }
+
+        scored_moves.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        scored_moves.into_iter().map(|(mv, _)| mv).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best_move(fen: &str, depth: i32) -> String {
+        let mut search_options = SearchOptions::default();
+        let mut args = vec![String::from("fen")];
+        args.extend(fen.split_whitespace().map(str::to_string));
+        search_options.set_position(&args).unwrap();
+
+        let transposition_table = Arc::new(TranspositionTable::new(search_options.hash_size));
+        let mut search_thread = SearchThread::new(
+            0,
+            Arc::clone(&transposition_table),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicBool::new(false)),
+        );
+        search_thread.initialize(&search_options, &transposition_table);
+        search_thread
+            .search(&search_options.board, &search_options.history, Depth(depth))
+            .map_or(String::from("0000"), |chess_move| chess_move.to_string())
+    }
+
+    #[test]
+    fn fixed_depth_best_moves() {
+        // search is deterministic, so any change of best move shows up here and must be intended
+        let positions = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "d2d4",
+            ),
+            (
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+                "d2d4",
+            ),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "e2a6",
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", "b4f4"),
+            (
+                "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+                "c3d5",
+            ),
+            ("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1", "d1d8"),
+            (
+                "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N2N2/PP2BPPP/R2QKB1R w KQ - 0 8",
+                "a1c1",
+            ),
+            ("8/8/4k3/8/2K5/8/3P4/8 w - - 0 1", "d2d4"),
+        ];
+        for (fen, expected) in positions {
+            assert_eq!(best_move(fen, 5), expected, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn null_move_zugzwang() {
+        // passing would be best for defending side after these moves, null move must not hide that
+        assert_eq!(
+            best_move("8/8/p1p5/1p5p/1P5p/8/PPP2K1p/4R1rk w - - 0 1", 12),
+            "e1f1"
+        );
+        assert_eq!(
+            best_move("8/8/1p1r1k2/p1pPN1p1/P3KnP1/1P6/8/3R4 b - - 0 1", 11),
+            "f4d5"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use chess::{
+    BitBoard, Board, ChessMove, Color, EMPTY, Piece, Square, get_bishop_moves, get_king_moves,
+    get_knight_moves, get_pawn_attacks, get_rook_moves,
+};
+
+use crate::piece_value::PieceValue;
+use crate::score::Score;
+
+const EXCHANGE_ORDER: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+pub fn static_exchange_evaluation(board: &Board, chess_move: ChessMove) -> Score {
+    /* Material balance in centi-pawns of capture sequence on destination square of move,
+    both sides always recapture with least valuable piece and may stop when it does not pay off. */
+    let piece_value = PieceValue::default();
+    let source = chess_move.get_source();
+    let target = chess_move.get_dest();
+    let mut occupied = *board.combined() ^ BitBoard::from_square(source);
+
+    let mut gains: Vec<Score> = vec![];
+    match board.piece_on(target) {
+        Some(victim) => gains.push(piece_value.get_piece_value(victim)),
+        None if board.piece_on(source) == Some(Piece::Pawn)
+            && source.get_file() != target.get_file() =>
+        {
+            // en passant, captured pawn is not on target square
+            let captured = Square::make_square(source.get_rank(), target.get_file());
+            occupied ^= BitBoard::from_square(captured);
+            gains.push(piece_value.pawn_value);
+        }
+        None => gains.push(Score(0)),
+    }
+
+    let mut piece_on_target = match chess_move.get_promotion() {
+        Some(promotion) => {
+            gains[0] += piece_value.get_piece_value(promotion) - piece_value.pawn_value;
+            promotion
+        }
+        None => board.piece_on(source).unwrap(),
+    };
+    let mut side = !board.side_to_move();
+
+    loop {
+        let attackers = attackers_to(board, target, occupied) & board.color_combined(side);
+        let Some((attacker, square)) = least_valuable_attacker(board, attackers) else {
+            break;
+        };
+
+        // king can only recapture if opponent has nothing left to take back with
+        if attacker == Piece::King {
+            let remaining = occupied ^ BitBoard::from_square(square);
+            if attackers_to(board, target, remaining) & board.color_combined(!side) != EMPTY {
+                break;
+            }
+        }
+
+        let previous = *gains.last().unwrap();
+        gains.push(piece_value.get_piece_value(piece_on_target) - previous);
+
+        occupied ^= BitBoard::from_square(square);
+        piece_on_target = attacker;
+        side = !side;
+    }
+
+    // each side picks better of recapturing and standing pat, going back from last capture
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let previous = gains.last_mut().unwrap();
+        *previous = -(-*previous).max(last);
+    }
+    gains[0]
+}
+
+fn attackers_to(board: &Board, square: Square, occupied: BitBoard) -> BitBoard {
+    /* All pieces of both colors attacking square, given occupancy for sliding pieces. */
+    let bishops_and_queens = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+    let rooks_and_queens = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    let white_pawns = board.pieces(Piece::Pawn) & board.color_combined(Color::White);
+    let black_pawns = board.pieces(Piece::Pawn) & board.color_combined(Color::Black);
+
+    (get_pawn_attacks(square, Color::Black, white_pawns)
+        | get_pawn_attacks(square, Color::White, black_pawns)
+        | (get_knight_moves(square) & board.pieces(Piece::Knight))
+        | (get_bishop_moves(square, occupied) & bishops_and_queens)
+        | (get_rook_moves(square, occupied) & rooks_and_queens)
+        | (get_king_moves(square) & board.pieces(Piece::King)))
+        & occupied
+}
+
+fn least_valuable_attacker(board: &Board, attackers: BitBoard) -> Option<(Piece, Square)> {
+    EXCHANGE_ORDER.iter().find_map(|&piece| {
+        let candidates = attackers & board.pieces(piece);
+        (candidates != EMPTY).then(|| (piece, candidates.to_square()))
+    })
+}
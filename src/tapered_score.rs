@@ -0,0 +1,96 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use crate::score::Score;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaperedScore {
+    pub middlegame: Score,
+    pub endgame: Score,
+}
+
+impl TaperedScore {
+    pub const ZERO: TaperedScore = TaperedScore::new(0, 0);
+    pub const MAX_PHASE: i32 = 24;
+
+    pub const fn new(middlegame: i32, endgame: i32) -> TaperedScore {
+        TaperedScore {
+            middlegame: Score(middlegame),
+            endgame: Score(endgame),
+        }
+    }
+
+    pub fn interpolate(self, phase: i32) -> Score {
+        /* Blend middlegame and endgame values, phase goes from 0 in bare endgame
+        to maximum with all pieces on board. */
+        let phase = phase.clamp(0, TaperedScore::MAX_PHASE);
+        (self.middlegame * phase + self.endgame * (TaperedScore::MAX_PHASE - phase))
+            / TaperedScore::MAX_PHASE
+    }
+}
+
+impl Neg for TaperedScore {
+    type Output = TaperedScore;
+
+    fn neg(self) -> TaperedScore {
+        TaperedScore {
+            middlegame: -self.middlegame,
+            endgame: -self.endgame,
+        }
+    }
+}
+
+impl Add for TaperedScore {
+    type Output = TaperedScore;
+
+    fn add(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore {
+            middlegame: self.middlegame + other.middlegame,
+            endgame: self.endgame + other.endgame,
+        }
+    }
+}
+
+impl Sub for TaperedScore {
+    type Output = TaperedScore;
+
+    fn sub(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore {
+            middlegame: self.middlegame - other.middlegame,
+            endgame: self.endgame - other.endgame,
+        }
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, other: TaperedScore) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for TaperedScore {
+    fn sub_assign(&mut self, other: TaperedScore) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i32> for TaperedScore {
+    type Output = TaperedScore;
+
+    fn mul(self, factor: i32) -> TaperedScore {
+        TaperedScore {
+            middlegame: self.middlegame * factor,
+            endgame: self.endgame * factor,
+        }
+    }
+}
+
+impl Div<i32> for TaperedScore {
+    type Output = TaperedScore;
+
+    fn div(self, divisor: i32) -> TaperedScore {
+        TaperedScore {
+            middlegame: self.middlegame / divisor,
+            endgame: self.endgame / divisor,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chess::{ALL_PIECES, ALL_SQUARES, ChessMove};
+
+use crate::depth::Depth;
+use crate::score::Score;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TableEntry {
+    pub key: u64,
+    pub depth: Depth,
+    pub bound: Bound,
+    pub score: Score,
+    pub best_move: Option<ChessMove>,
+}
+
+struct Slot {
+    // key is stored xor-ed with data, so that entry torn by concurrent writes fails key check
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    size_mb: usize,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        TranspositionTable {
+            slots: (0..TranspositionTable::capacity(size_mb))
+                .map(|_| Slot {
+                    key: AtomicU64::new(0),
+                    data: AtomicU64::new(0),
+                })
+                .collect(),
+            size_mb,
+        }
+    }
+
+    pub fn size_mb(&self) -> usize {
+        self.size_mb
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TableEntry> {
+        /* Return stored entry for position hash, if there is one. */
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        Some(TranspositionTable::unpack(key, data))
+    }
+
+    pub fn store(&self, entry: TableEntry) {
+        /* Store entry, same position is only overwritten by equal or deeper search. */
+        let slot = &self.slots[self.index(entry.key)];
+        if let Some(existing) = self.probe(entry.key)
+            && existing.depth > entry.depth
+        {
+            return;
+        }
+
+        let data = TranspositionTable::pack(&entry);
+        slot.key.store(entry.key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+
+    fn capacity(size_mb: usize) -> usize {
+        (size_mb.max(1) * 1024 * 1024 / size_of::<Slot>()).max(1)
+    }
+
+    fn pack(entry: &TableEntry) -> u64 {
+        /* Pack entry into 64 bits: score (16) | depth (8) | bound (2) | move (16). */
+        let bound: u64 = match entry.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        let chess_move: u64 = match entry.best_move {
+            Some(chess_move) => {
+                let promotion = chess_move.get_promotion().map_or(0, |p| p.to_index() + 1);
+                (1 << 15)
+                    | (chess_move.get_source().to_index() << 9) as u64
+                    | (chess_move.get_dest().to_index() << 3) as u64
+                    | promotion as u64
+            }
+            None => 0,
+        };
+
+        (entry.score.0 as i16 as u16) as u64
+            | (entry.depth.0.clamp(0, 255) as u64) << 16
+            | bound << 24
+            | chess_move << 26
+    }
+
+    fn unpack(key: u64, data: u64) -> TableEntry {
+        let bound = match (data >> 24) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let chess_move = (data >> 26) & 0xFFFF;
+        let best_move = (chess_move & (1 << 15) != 0).then(|| {
+            let promotion = (chess_move & 0b111) as usize;
+            ChessMove::new(
+                ALL_SQUARES[((chess_move >> 9) & 0b11_1111) as usize],
+                ALL_SQUARES[((chess_move >> 3) & 0b11_1111) as usize],
+                (promotion > 0).then(|| ALL_PIECES[promotion - 1]),
+            )
+        });
+
+        TableEntry {
+            key,
+            depth: Depth(((data >> 16) & 0xFF) as i32),
+            bound,
+            score: Score(data as u16 as i16 as i32),
+            best_move,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UciError {
+    MissingValue(String),
+    InvalidValue { name: String, value: String },
+    MissingPosition,
+    InvalidFen(String),
+    InvalidMove(String),
+    IllegalMove { chess_move: String, fen: String },
+    MissingTime,
+    MissingOptionName,
+    UnknownOption(String),
+    InvalidFile { path: String, error: String },
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::MissingValue(name) => write!(formatter, "missing value for {}", name),
+            UciError::InvalidValue { name, value } => {
+                write!(formatter, "invalid value {} for {}", value, name)
+            }
+            UciError::MissingPosition => write!(formatter, "position needs startpos or fen"),
+            UciError::InvalidFen(fen) => write!(formatter, "invalid fen {}", fen),
+            UciError::InvalidMove(chess_move) => write!(formatter, "invalid move {}", chess_move),
+            UciError::IllegalMove { chess_move, fen } => {
+                write!(formatter, "illegal move {} in position {}", chess_move, fen)
+            }
+            UciError::MissingTime => write!(formatter, "no time given for side to move"),
+            UciError::MissingOptionName => write!(formatter, "setoption needs option name"),
+            UciError::UnknownOption(name) => write!(formatter, "unknown option {}", name),
+            UciError::InvalidFile { path, error } => {
+                write!(formatter, "unable to load {}: {}", path, error)
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use std::io;
 use std::sync::mpsc::Sender;
 
+use crate::bench::bench_depth;
 use crate::engine_command::EngineCommand;
 use crate::infra::capitalize_first_letter;
+use crate::perft;
 use crate::search_options::SearchOptions;
+use crate::uci_error::UciError;
 use crate::version::display_version;
 
 pub struct UciProtocol {
@@ -37,9 +40,12 @@ impl UciProtocol {
                 "isready" => self.is_ready(),
                 "go" => self.go(args),
                 "stop" => self.stop(),
+                "ponderhit" => self.ponder_hit(),
                 "setoption" => self.set_option(args),
                 "ucinewgame" => self.new_game(),
                 "position" => self.position(args),
+                "perft" => self.perft(args),
+                "bench" => self.bench(args),
                 "quit" => {
                     self.quit();
                     break;
@@ -73,7 +79,15 @@ impl UciProtocol {
     }
 
     fn go(&mut self, args: &[String]) {
-        self.search_options.set_search_parameters(args);
+        // non-standard go perft is answered directly, without engine
+        if args.first().is_some_and(|r| r == "perft") {
+            self.perft(&args[1..]);
+            return;
+        }
+        if let Err(error) = self.search_options.set_search_parameters(args) {
+            UciProtocol::report_error(&error);
+            return;
+        }
         self.sender
             .send(EngineCommand::go(self.search_options.clone()))
             .expect("Go command could not be sent.");
@@ -85,15 +99,69 @@ impl UciProtocol {
             .expect("Stop command could not be sent.");
     }
 
+    fn ponder_hit(&mut self) {
+        self.sender
+            .send(EngineCommand::ponder_hit())
+            .expect("Ponderhit command could not be sent.");
+    }
+
     fn set_option(&mut self, args: &[String]) {
-        self.search_options.set_option(args);
+        match self.search_options.set_option(args) {
+            Ok(true) => self.clear_hash(),
+            Ok(false) => {}
+            Err(error) => UciProtocol::report_error(&error),
+        }
     }
 
     fn new_game(&mut self) {
         self.search_options.reset();
+        self.clear_hash();
+    }
+
+    fn clear_hash(&mut self) {
+        self.sender
+            .send(EngineCommand::clear())
+            .expect("Clear command could not be sent.");
     }
 
     fn position(&mut self, args: &[String]) {
-        self.search_options.set_position(args);
+        if let Err(error) = self.search_options.set_position(args) {
+            UciProtocol::report_error(&error);
+        }
+    }
+
+    fn bench(&mut self, args: &[String]) {
+        /* Non-standard bench command, fixed depth search of built-in positions. */
+        let depth = match bench_depth(args) {
+            Ok(depth) => depth,
+            Err(error) => {
+                UciProtocol::report_error(&error);
+                return;
+            }
+        };
+        let mut options = self.search_options.clone();
+        options.set_bench_parameters(depth);
+        self.sender
+            .send(EngineCommand::bench(options))
+            .expect("Bench command could not be sent.");
+    }
+
+    fn perft(&self, args: &[String]) {
+        /* Non-standard perft command, counts per root move for given depth or standard suite. */
+        match args.first().map(String::as_str) {
+            Some("suite") => perft::run_suite(),
+            Some(value) => match value.parse::<usize>() {
+                Ok(depth) => perft::divide(&self.search_options.board, depth),
+                Err(_) => UciProtocol::report_error(&UciError::InvalidValue {
+                    name: String::from("perft"),
+                    value: value.to_string(),
+                }),
+            },
+            None => UciProtocol::report_error(&UciError::MissingValue(String::from("perft"))),
+        }
+    }
+
+    fn report_error(error: &UciError) {
+        println!("info string error: {}", error);
     }
 }